@@ -1,6 +1,6 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	AccountId, Aswap, AswapFeeDestination, AuraConfig, BalancesConfig, GenesisConfig,
+	GrandpaConfig, Signature, SudoConfig, SystemConfig, EXISTENTIAL_DEPOSIT, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -138,8 +138,18 @@ fn testnet_genesis(
 			code: wasm_binary.to_vec(),
 		},
 		balances: BalancesConfig {
-			// Configure endowed accounts with initial balance of 1 << 60.
-			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
+			// Configure endowed accounts with initial balance of 1 << 60, and the aswap pallet
+			// and fee accounts with the existential deposit so the pallet account stays alive
+			// when the last lock it holds is paid out and fees below it can be received.
+			balances: endowed_accounts
+				.iter()
+				.cloned()
+				.map(|k| (k, 1 << 60))
+				.chain([
+					(Aswap::account_id(), EXISTENTIAL_DEPOSIT),
+					(AswapFeeDestination::get(), EXISTENTIAL_DEPOSIT),
+				])
+				.collect(),
 		},
 		aura: AuraConfig {
 			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
@@ -0,0 +1,160 @@
+//! RPC interface for the aswap pallet.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Codec;
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use pallet_aswap_runtime_api::{CustodyAudit, LockDetails, LockStatus, SwapAsset};
+use serde::{de::DeserializeOwned, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{Bytes, H256};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_aswap_runtime_api::AswapApi as AswapRuntimeApi;
+
+/// default and maximum number of locks returned by `aswap_locksForAccount`
+const MAX_LOCKS_PER_PAGE: u32 = 100;
+
+/// Aswap RPC methods.
+#[rpc(client, server)]
+pub trait AswapApi<BlockHash, AccountId, AssetId, LockDetails, CustodyAudit> {
+	/// details of the lock `tx_id`
+	#[method(name = "aswap_lockDetails")]
+	fn lock_details(&self, tx_id: H256, at: Option<BlockHash>) -> RpcResult<Option<LockDetails>>;
+
+	/// secret revealed when the lock `tx_id` was unlocked
+	#[method(name = "aswap_knownSecret")]
+	fn known_secret(&self, tx_id: H256, at: Option<BlockHash>) -> RpcResult<Option<Bytes>>;
+
+	/// pending locks sent or received by `account`. Pass the tx_id of the last lock of a page as
+	/// `cursor` to get the next one.
+	#[method(name = "aswap_locksForAccount")]
+	fn locks_for_account(
+		&self,
+		account: AccountId,
+		cursor: Option<H256>,
+		limit: Option<u32>,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<LockDetails>>;
+
+	/// status of the lock `tx_id`
+	#[method(name = "aswap_status")]
+	fn status(&self, tx_id: H256, at: Option<BlockHash>) -> RpcResult<Option<LockStatus>>;
+
+	/// amount of the pending locks of `asset` compared with the balance of the accounts and
+	/// holds keeping them
+	#[method(name = "aswap_audit")]
+	fn audit(&self, asset: SwapAsset<AssetId>, at: Option<BlockHash>) -> RpcResult<CustodyAudit>;
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The call to runtime failed.
+	RuntimeError,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::RuntimeError => 1,
+		}
+	}
+}
+
+/// Provides RPC methods to query the swaps of the aswap pallet.
+pub struct Aswap<C, Block> {
+	client: Arc<C>,
+	_marker: PhantomData<Block>,
+}
+
+impl<C, Block> Aswap<C, Block> {
+	/// Creates a new instance of the Aswap Rpc helper.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+fn runtime_error(message: &str, e: impl std::fmt::Debug) -> jsonrpsee::core::Error {
+	CallError::Custom(ErrorObject::owned(
+		Error::RuntimeError.into(),
+		message,
+		Some(format!("{:?}", e)),
+	))
+	.into()
+}
+
+impl<C, Block, AccountId, AssetId, Balance, BlockNumber, Moment>
+	AswapApiServer<
+		<Block as BlockT>::Hash,
+		AccountId,
+		AssetId,
+		LockDetails<Balance, AssetId, AccountId, BlockNumber, Moment>,
+		CustodyAudit<Balance>,
+	> for Aswap<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: AswapRuntimeApi<Block, AccountId, AssetId, Balance, BlockNumber, Moment>,
+	AccountId: Codec + DeserializeOwned + Send + Sync + 'static,
+	AssetId: Codec + DeserializeOwned + Send + Sync + 'static,
+	Balance: Codec + Send + Sync + 'static,
+	BlockNumber: Codec + Send + Sync + 'static,
+	Moment: Codec + Send + Sync + 'static,
+	LockDetails<Balance, AssetId, AccountId, BlockNumber, Moment>: Serialize,
+	CustodyAudit<Balance>: Serialize,
+{
+	fn lock_details(
+		&self,
+		tx_id: H256,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Option<LockDetails<Balance, AssetId, AccountId, BlockNumber, Moment>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.lock_details(&at, tx_id.into())
+			.map_err(|e| runtime_error("Unable to query lock details.", e))
+	}
+
+	fn known_secret(&self, tx_id: H256, at: Option<Block::Hash>) -> RpcResult<Option<Bytes>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.known_secret(&at, tx_id.into())
+			.map(|secret| secret.map(Into::into))
+			.map_err(|e| runtime_error("Unable to query known secret.", e))
+	}
+
+	fn locks_for_account(
+		&self,
+		account: AccountId,
+		cursor: Option<H256>,
+		limit: Option<u32>,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Vec<LockDetails<Balance, AssetId, AccountId, BlockNumber, Moment>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let limit = limit.unwrap_or(MAX_LOCKS_PER_PAGE).min(MAX_LOCKS_PER_PAGE);
+		api.locks_for_account(&at, account, cursor.map(Into::into), limit)
+			.map_err(|e| runtime_error("Unable to query locks for account.", e))
+	}
+
+	fn status(&self, tx_id: H256, at: Option<Block::Hash>) -> RpcResult<Option<LockStatus>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.status(&at, tx_id.into())
+			.map_err(|e| runtime_error("Unable to query lock status.", e))
+	}
+
+	fn audit(
+		&self,
+		asset: SwapAsset<AssetId>,
+		at: Option<Block::Hash>,
+	) -> RpcResult<CustodyAudit<Balance>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.audit(&at, asset).map_err(|e| runtime_error("Unable to audit asset.", e))
+	}
+}
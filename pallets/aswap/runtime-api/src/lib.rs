@@ -0,0 +1,39 @@
+//! Runtime API definition for the aswap pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// The `too_many_arguments` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::too_many_arguments)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+pub use pallet_aswap::{CustodyAudit, LockDetails, LockStatus, SwapAsset};
+
+sp_api::decl_runtime_apis! {
+	/// queries over the swaps held by the aswap pallet
+	pub trait AswapApi<AccountId, AssetId, Balance, BlockNumber, Moment>
+	where
+		AccountId: Codec,
+		AssetId: Codec,
+		Balance: Codec,
+		BlockNumber: Codec,
+		Moment: Codec,
+	{
+		/// details of the lock `tx_id`
+		fn lock_details(tx_id: [u8; 32]) -> Option<LockDetails<Balance, AssetId, AccountId, BlockNumber, Moment>>;
+		/// secret revealed when the lock `tx_id` was unlocked
+		fn known_secret(tx_id: [u8; 32]) -> Option<Vec<u8>>;
+		/// at most `limit` pending locks sent or received by `account`, ordered by tx_id and
+		/// starting after `cursor`
+		fn locks_for_account(
+			account: AccountId,
+			cursor: Option<[u8; 32]>,
+			limit: u32,
+		) -> Vec<LockDetails<Balance, AssetId, AccountId, BlockNumber, Moment>>;
+		/// status of the lock `tx_id`
+		fn status(tx_id: [u8; 32]) -> Option<LockStatus>;
+		/// amount of the pending locks of `asset` compared with the balance of the accounts and
+		/// holds keeping them
+		fn audit(asset: SwapAsset<AssetId>) -> CustodyAudit<Balance>;
+	}
+}
@@ -1,20 +1,354 @@
-//! Benchmarking setup for pallet-template
+//! Benchmarking setup for pallet-aswap
 
 use super::*;
 
 #[allow(unused)]
-use crate::Pallet as Template;
-use frame_benchmarking::{benchmarks, whitelisted_caller};
+use crate::Pallet as Aswap;
+use codec::{Decode, MaxEncodedLen};
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::{
+	sp_runtime::{
+		traits::{IdentifyAccount, TrailingZeroInput},
+		Permill,
+	},
+	traits::{
+		fungibles::{Create, Inspect, Mutate},
+		Currency, EnsureOrigin, Get,
+	},
+};
 use frame_system::RawOrigin;
+use sp_std::{vec, vec::Vec};
+
+const SEED: u32 = 0;
+const SECRET: [u8; 32] = [1u8; 32];
+
+/// allows locks of `asset` without limits, so benchmarks don't depend on `RequireAssetPolicy`.
+fn allow_asset<T: Config>(asset: SwapAssetOf<T>) {
+	AssetPolicies::<T>::insert(asset, AssetPolicy { enabled: true, ..Default::default() });
+}
+
+/// gives `who` the native balance to reserve the deposit of one lock.
+fn fund_deposit<T: Config>(who: &T::AccountId) {
+	let deposit = Aswap::<T>::deposit_for(PreimageOf::<T>::max_encoded_len());
+	T::Currency::make_free_balance_be(who, T::Currency::minimum_balance() + deposit);
+}
+
+/// creates a sufficient asset charging a fee on unlock, funds `holder` with it and gives the
+/// pallet account the minimum balance it needs to stay alive.
+fn create_asset<T: Config>(holder: &T::AccountId) -> AssetIdOf<T> {
+	let asset_id: AssetIdOf<T> = Decode::decode(&mut TrailingZeroInput::zeroes())
+		.expect("infinite length input; no invalid inputs for type; qed");
+	let min_balance: AssetBalanceOf<T> = 1u32.into();
+	if !T::Fungibles::asset_exists(asset_id) {
+		T::Fungibles::create(asset_id, holder.clone(), true, min_balance)
+			.expect("asset is created");
+	}
+	T::Fungibles::mint_into(asset_id, holder, 2_000_000u32.into()).expect("holder is funded");
+	T::Fungibles::mint_into(asset_id, &Aswap::<T>::account_id(), min_balance)
+		.expect("pallet account is funded");
+	allow_asset::<T>(SwapAsset::Asset(asset_id));
+	FeePolicies::<T>::insert(
+		SwapAsset::Asset(asset_id),
+		FeePolicy { rate: Permill::from_percent(1), ..Default::default() },
+	);
+	asset_id
+}
+
+/// `tx_id` argument of a lock lasting `timelock` blocks and the id it is stored with. The pallet
+/// derives the id when `DeriveTxIds` is set, otherwise `index` tells apart the ids of several
+/// locks.
+fn tx_id_for<T: Config>(
+	sender: &T::AccountId,
+	recipient: &T::AccountId,
+	hashlock: [u8; 32],
+	asset: SwapAssetOf<T>,
+	amount: AssetBalanceOf<T>,
+	timelock: BlockNumberOf<T>,
+	index: u32,
+) -> (Option<[u8; 32]>, [u8; 32]) {
+	if T::DeriveTxIds::get() {
+		let expiry = Expiry::AtBlock(frame_system::Pallet::<T>::block_number() + timelock);
+		(None, Aswap::<T>::derive_tx_id(sender, recipient, hashlock, asset, amount, &expiry))
+	} else {
+		let mut tx_id = [1u8; 32];
+		tx_id[..4].copy_from_slice(&index.to_le_bytes());
+		(Some(tx_id), tx_id)
+	}
+}
+
+/// locks `1_000_000` units of a fresh asset from `sender` to `recipient` for `MinTimelock`
+/// blocks, `index` tells apart the ids of several locks. `sender` can open any number of locks.
+fn create_lock<T: Config>(
+	sender: T::AccountId,
+	recipient: T::AccountId,
+	hash_algorithm: HashAlgorithm,
+	preimage: &[u8],
+	index: u32,
+) -> [u8; 32] {
+	let asset_id = create_asset::<T>(&sender);
+	fund_deposit::<T>(&sender);
+	OpenLockLimits::<T>::insert(&sender, u32::MAX);
+	let hashlock = hash_algorithm.hash(preimage);
+	let amount: AssetBalanceOf<T> = 1_000_000u32.into();
+	let timelock = T::MinTimelock::get();
+	let (tx_id, stored_tx_id) = tx_id_for::<T>(
+		&sender,
+		&recipient,
+		hashlock,
+		SwapAsset::Asset(asset_id),
+		amount,
+		timelock,
+		index,
+	);
+	Aswap::<T>::lock(
+		RawOrigin::Signed(sender).into(),
+		tx_id,
+		recipient,
+		hashlock,
+		hash_algorithm,
+		Timelock::Blocks(timelock),
+		asset_id,
+		amount,
+	)
+	.expect("lock is created");
+	stored_tx_id
+}
 
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
+	lock {
 		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+		let recipient: T::AccountId = account("recipient", 0, SEED);
+		let asset_id = create_asset::<T>(&caller);
+		fund_deposit::<T>(&caller);
+		let hashlock = HashAlgorithm::Sha2_256.hash(&SECRET);
+		let timelock = T::MinTimelock::get();
+		let amount: AssetBalanceOf<T> = 1_000_000u32.into();
+		let (tx_id, stored_tx_id) = tx_id_for::<T>(
+			&caller,
+			&recipient,
+			hashlock,
+			SwapAsset::Asset(asset_id),
+			amount,
+			timelock,
+			0,
+		);
+	}: _(
+		RawOrigin::Signed(caller),
+		tx_id,
+		recipient,
+		hashlock,
+		HashAlgorithm::Sha2_256,
+		Timelock::Blocks(timelock),
+		asset_id,
+		amount
+	)
+	verify {
+		assert!(LockTransactions::<T>::contains_key(stored_tx_id));
+	}
+
+	lock_native {
+		let caller: T::AccountId = whitelisted_caller();
+		let recipient: T::AccountId = account("recipient", 0, SEED);
+		T::Currency::make_free_balance_be(&caller, 2_000_000_000u32.into());
+		T::Currency::make_free_balance_be(
+			&Aswap::<T>::account_id(),
+			T::Currency::minimum_balance(),
+		);
+		allow_asset::<T>(SwapAsset::Native);
+		let hashlock = HashAlgorithm::Sha2_256.hash(&SECRET);
+		let timelock = T::MinTimelock::get();
+		let amount: BalanceOf<T> = 1_000_000u32.into();
+		let (tx_id, stored_tx_id) =
+			tx_id_for::<T>(&caller, &recipient, hashlock, SwapAsset::Native, amount, timelock, 0);
+	}: _(RawOrigin::Signed(caller), tx_id, recipient, hashlock, HashAlgorithm::Sha2_256, Timelock::Blocks(timelock), amount)
+	verify {
+		assert!(LockTransactions::<T>::contains_key(stored_tx_id));
+	}
+
+	unlock {
+		let p in 1 .. T::MaxPreimageLen::get();
+		let caller: T::AccountId = whitelisted_caller();
+		let recipient: T::AccountId = account("recipient", 0, SEED);
+		let preimage: Vec<u8> =
+			if T::StrictSecretLength::get() { SECRET.to_vec() } else { vec![1u8; p as usize] };
+		// hash160 hashes the preimage twice, the most expensive check.
+		let tx_id =
+			create_lock::<T>(caller, recipient.clone(), HashAlgorithm::Hash160, &preimage, 0);
+	}: _(RawOrigin::Signed(recipient), tx_id, preimage)
+	verify {
+		assert!(KnownSecrets::<T>::contains_key(tx_id));
+	}
+
+	unlock_for {
+		let p in 1 .. T::MaxPreimageLen::get();
+		let caller: T::AccountId = whitelisted_caller();
+		let relayer: T::AccountId = account("relayer", 0, SEED);
+		let signer = T::BenchmarkHelper::signer();
+		let recipient = signer.clone().into_account();
+		let preimage: Vec<u8> =
+			if T::StrictSecretLength::get() { SECRET.to_vec() } else { vec![1u8; p as usize] };
+		let tx_id = create_lock::<T>(caller, recipient, HashAlgorithm::Hash160, &preimage, 0);
+		let relayer_fee: AssetBalanceOf<T> = 1_000u32.into();
+		let signature =
+			T::BenchmarkHelper::sign(&signer, &Aswap::<T>::unlock_for_payload(tx_id, relayer_fee));
+	}: _(RawOrigin::Signed(relayer), tx_id, preimage, signature, relayer_fee)
+	verify {
+		assert!(KnownSecrets::<T>::contains_key(tx_id));
+	}
+
+	cancel {
+		let caller: T::AccountId = whitelisted_caller();
+		let recipient: T::AccountId = account("recipient", 0, SEED);
+		let tx_id =
+			create_lock::<T>(caller.clone(), recipient, HashAlgorithm::Sha2_256, &SECRET, 0);
+		let expiration_block = LockTransactions::<T>::get(tx_id).unwrap().expiration_block;
+		frame_system::Pallet::<T>::set_block_number(expiration_block);
+	}: _(RawOrigin::Signed(caller), tx_id)
+	verify {
+		assert!(matches!(
+			LockTransactions::<T>::get(tx_id).unwrap().status,
+			SwapStatus::Refunded { .. }
+		));
+	}
+
+	refund_expired {
+		let n in 1 .. 100;
+		let caller: T::AccountId = whitelisted_caller();
+		let recipient: T::AccountId = account("recipient", 0, SEED);
+		let tx_ids: Vec<[u8; 32]> = (0..n)
+			.map(|index| {
+				create_lock::<T>(
+					caller.clone(),
+					recipient.clone(),
+					HashAlgorithm::Sha2_256,
+					// distinct hashlocks, so `MinTimelockMargin` doesn't apply.
+					&index.to_le_bytes(),
+					index,
+				)
+			})
+			.collect();
+		let expiration_block = LockTransactions::<T>::get(tx_ids[0]).unwrap().expiration_block;
+		frame_system::Pallet::<T>::set_block_number(expiration_block);
+		ExpiryCursor::<T>::put(expiration_block);
+	}: {
+		Aswap::<T>::refund_expired(expiration_block, n);
+	}
+	verify {
+		for tx_id in tx_ids {
+			assert!(matches!(
+				LockTransactions::<T>::get(tx_id).unwrap().status,
+				SwapStatus::Refunded { .. }
+			));
+		}
+	}
+
+	pause {
+		let origin = T::PauseOrigin::try_successful_origin()
+			.expect("PauseOrigin has a successful origin");
+	}: _<T::RuntimeOrigin>(origin, PauseMode::All)
+	verify {
+		assert_eq!(Paused::<T>::get(), Some(PauseMode::All));
+	}
+
+	unpause {
+		let origin = T::PauseOrigin::try_successful_origin()
+			.expect("PauseOrigin has a successful origin");
+		Paused::<T>::put(PauseMode::All);
+	}: _<T::RuntimeOrigin>(origin)
+	verify {
+		assert!(Paused::<T>::get().is_none());
+	}
+
+	set_asset_policy {
+		let origin = T::AdminOrigin::try_successful_origin()
+			.expect("AdminOrigin has a successful origin");
+		let policy = AssetPolicy {
+			enabled: true,
+			min_amount: 1u32.into(),
+			max_amount: Some(1_000_000u32.into()),
+			tvl_cap: Some(10_000_000u32.into()),
+		};
+	}: _<T::RuntimeOrigin>(origin, SwapAsset::Native, policy)
+	verify {
+		assert_eq!(AssetPolicies::<T>::get(SwapAsset::Native), Some(policy));
+	}
+
+	set_open_lock_limit {
+		let origin = T::AdminOrigin::try_successful_origin()
+			.expect("AdminOrigin has a successful origin");
+		let who: T::AccountId = account("market_maker", 0, SEED);
+	}: _<T::RuntimeOrigin>(origin, who.clone(), Some(1_000))
+	verify {
+		assert_eq!(OpenLockLimits::<T>::get(who), Some(1_000));
+	}
+
+	set_fee_policy {
+		let origin = T::AdminOrigin::try_successful_origin()
+			.expect("AdminOrigin has a successful origin");
+		let policy = FeePolicy {
+			rate: Permill::from_percent(1),
+			flat: 1_000u32.into(),
+			scale_by_duration: true,
+		};
+	}: _<T::RuntimeOrigin>(origin, SwapAsset::Native, Some(policy))
+	verify {
+		assert_eq!(FeePolicies::<T>::get(SwapAsset::Native), Some(policy));
+	}
+
+	remove_asset_policy {
+		let origin = T::AdminOrigin::try_successful_origin()
+			.expect("AdminOrigin has a successful origin");
+		allow_asset::<T>(SwapAsset::Native);
+	}: _<T::RuntimeOrigin>(origin, SwapAsset::Native)
+	verify {
+		assert!(AssetPolicies::<T>::get(SwapAsset::Native).is_none());
+	}
+
+	reap {
+		let caller: T::AccountId = whitelisted_caller();
+		let recipient: T::AccountId = account("recipient", 0, SEED);
+		let tx_id =
+			create_lock::<T>(caller.clone(), recipient, HashAlgorithm::Sha2_256, &SECRET, 0);
+		let expiration_block = LockTransactions::<T>::get(tx_id).unwrap().expiration_block;
+		frame_system::Pallet::<T>::set_block_number(expiration_block);
+		Aswap::<T>::do_refund(tx_id).expect("lock is refunded");
+		frame_system::Pallet::<T>::set_block_number(expiration_block + T::RetentionPeriod::get());
+	}: _(RawOrigin::Signed(caller), tx_id)
+	verify {
+		assert!(!LockTransactions::<T>::contains_key(tx_id));
+	}
+
+	prune_settled {
+		let n in 1 .. 100;
+		let caller: T::AccountId = whitelisted_caller();
+		let recipient: T::AccountId = account("recipient", 0, SEED);
+		let tx_ids: Vec<[u8; 32]> = (0..n)
+			.map(|index| {
+				create_lock::<T>(
+					caller.clone(),
+					recipient.clone(),
+					HashAlgorithm::Sha2_256,
+					&index.to_le_bytes(),
+					index,
+				)
+			})
+			.collect();
+		let expiration_block = LockTransactions::<T>::get(tx_ids[0]).unwrap().expiration_block;
+		frame_system::Pallet::<T>::set_block_number(expiration_block);
+		for tx_id in tx_ids.iter() {
+			Aswap::<T>::do_refund(*tx_id).expect("lock is refunded");
+		}
+		let now = expiration_block + T::RetentionPeriod::get();
+		frame_system::Pallet::<T>::set_block_number(now);
+		PruneCursor::<T>::put(expiration_block);
+	}: {
+		Aswap::<T>::prune_settled(now, n);
+	}
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		for tx_id in tx_ids {
+			assert!(!LockTransactions::<T>::contains_key(tx_id));
+		}
 	}
 
-	impl_benchmark_test_suite!(Template, crate::mock::new_test_ext(), crate::mock::Test);
+	impl_benchmark_test_suite!(Aswap, crate::mock::new_test_ext(), crate::mock::Test);
 }
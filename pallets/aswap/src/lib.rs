@@ -14,21 +14,38 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+pub mod migrations;
+pub mod weights;
+pub use weights::*;
+
 #[frame_support::pallet]
 pub mod pallet {
+	use crate::weights::WeightInfo;
 	use frame_support::{
 		inherent::Vec,
 		pallet_prelude::{DispatchResult, *},
 		sp_io::hashing,
-		sp_runtime::traits::{AccountIdConversion, Zero},
+		sp_runtime::{
+			traits::{
+				AccountIdConversion, CheckedAdd, CheckedSub, Hash as HashT, IdentifyAccount, One,
+				Saturating, Verify, Zero,
+			},
+			Perbill, Permill, SaturatedConversion,
+		},
+		storage::with_storage_layer,
 		traits::{
 			fungibles::{self, *},
-			tokens::WithdrawConsequence,
-			Currency, LockableCurrency, ReservableCurrency,
+			tokens::{DepositConsequence, WithdrawConsequence},
+			BalanceStatus, Currency, ExistenceRequirement, LockableCurrency,
+			NamedReservableCurrency, ReservableCurrency, Time, WithdrawReasons,
 		},
 		PalletId,
 	};
 	use frame_system::pallet_prelude::*;
+	use ripemd::{Digest, Ripemd160};
+	#[cfg(feature = "std")]
+	use serde::{Deserialize, Serialize};
+	use sp_std::collections::btree_set::BTreeSet;
 	pub type BlockNumberOf<T> = <T as frame_system::Config>::BlockNumber;
 	pub type BalanceOf<T> =
 		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
@@ -42,10 +59,30 @@ pub mod pallet {
 	pub type AssetPriceOf<T> = <<T as Config>::Fungibles as fungibles::Inspect<
 		<T as frame_system::Config>::AccountId,
 	>>::Balance;
+	pub type SwapAssetOf<T> = SwapAsset<AssetIdOf<T>>;
+	pub type MomentOf<T> = <<T as Config>::Time as Time>::Moment;
+	pub type ExpiryOf<T> = Expiry<BlockNumberOf<T>, MomentOf<T>>;
+	pub type TimelockOf<T> = Timelock<BlockNumberOf<T>, MomentOf<T>>;
+	/// type for modeling a revealed secret
+	pub type PreimageOf<T> = BoundedVec<u8, <T as Config>::MaxPreimageLen>;
+	/// type for modeling a secret revealed before `MaxPreimageLen` bounded them
+	pub type LegacyPreimageOf<T> = BoundedVec<u8, <T as Config>::MaxLegacyPreimageLen>;
+
+	/// Signs the `unlock_for` claims of the benchmarks.
+	#[cfg(feature = "runtime-benchmarks")]
+	pub trait BenchmarkHelper<Public, Signature> {
+		/// key of a recipient able to sign claims
+		fn signer() -> Public;
+		/// signature of `message` by `signer`
+		fn sign(signer: &Public, message: &[u8]) -> Signature;
+	}
+
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(13);
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
-	#[pallet::without_storage_info]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
@@ -61,36 +98,363 @@ pub mod pallet {
 		/// Type to access the Balances Pallet.
 		type Currency: Currency<Self::AccountId>
 			+ ReservableCurrency<Self::AccountId>
+			+ NamedReservableCurrency<Self::AccountId, ReserveIdentifier = [u8; 8]>
 			+ LockableCurrency<Self::AccountId>;
 
-		/// Type to access the Assets Pallet.
-		type Fungibles: fungibles::Inspect<Self::AccountId>
-			+ fungibles::Mutate<Self::AccountId>
+		/// Base deposit reserved from the sender of every lock for the storage it uses.
+		#[pallet::constant]
+		type DepositBase: Get<BalanceOf<Self>>;
+
+		/// Deposit reserved from the sender of a lock per byte of the lock and of its secret.
+		#[pallet::constant]
+		type DepositPerByte: Get<BalanceOf<Self>>;
+
+		/// When true, only 32-byte preimages can unlock a swap. Bitcoin HTLC scripts enforce the
+		/// same rule, so a shorter or longer secret revealed here could not claim the other leg.
+		#[pallet::constant]
+		type StrictSecretLength: Get<bool>;
+
+		/// Maximum length of the preimage revealed by `unlock`. Longer preimages are rejected so
+		/// `KnownSecrets` stays bounded.
+		#[pallet::constant]
+		type MaxPreimageLen: Get<u32>;
+
+		/// Maximum length of the secrets longer than `MaxPreimageLen`, revealed before it bounded
+		/// them, that the v5 migration keeps in `OversizedSecrets`.
+		#[pallet::constant]
+		type MaxLegacyPreimageLen: Get<u32>;
+
+		/// When true, `unlock` fails with `Expired` once the timelock has expired, so the recipient
+		/// can't race the sender's refund. Two-chain swaps rely on this to stay atomic.
+		#[pallet::constant]
+		type ClaimBeforeExpiry: Get<bool>;
+
+		/// Minimum number of blocks a lock must last, so the counterparty always has time to act.
+		#[pallet::constant]
+		type MinTimelock: Get<Self::BlockNumber>;
+
+		/// Maximum number of blocks a lock can last.
+		#[pallet::constant]
+		type MaxTimelock: Get<Self::BlockNumber>;
+
+		/// Minimum number of blocks between the expiry of a lock and the expiry of the pending lock
+		/// it answers: one with the same hashlock sent by its recipient to its sender. The lock
+		/// answering a swap must expire first, so the initiator can't claim it right before
+		/// refunding its own side. Locks of third parties under the same hashlock are ignored, so
+		/// they can't push the deadline of the answer. Zero disables it.
+		#[pallet::constant]
+		type MinTimelockMargin: Get<Self::BlockNumber>;
+
+		/// When true, the pallet derives the id of every lock from its parameters and a nonce of
+		/// the sender, so ids can't be squatted by front-running a `lock`. Callers must then
+		/// leave `tx_id` empty. When false, callers must provide it.
+		#[pallet::constant]
+		type DeriveTxIds: Get<bool>;
+
+		/// Clock used by locks that expire at a timestamp.
+		type Time: Time;
+
+		/// Expected time between two blocks, in the unit of `Config::Time`. Used to estimate the
+		/// block where a time-based lock expires.
+		#[pallet::constant]
+		type ExpectedBlockTime: Get<MomentOf<Self>>;
+
+		/// Maximum number of expired locks refunded automatically in `on_initialize`. Remaining
+		/// expired locks are refunded in `on_idle` or in the following blocks.
+		#[pallet::constant]
+		type MaxAutoRefundsPerBlock: Get<u32>;
+
+		/// Number of blocks a claimed or refunded lock and its secret are kept before they are
+		/// pruned in `on_idle` or by `reap`.
+		#[pallet::constant]
+		type RetentionPeriod: Get<Self::BlockNumber>;
+
+		/// Account receiving the fees charged on unlock by the `FeePolicies` of the assets.
+		type FeeDestination: Get<Self::AccountId>;
+
+		/// When true, a lock can't take the sender's balance below the existential deposit or the
+		/// minimum balance of the asset. When false, a lock can take the whole balance and the
+		/// sender's account may be reaped.
+		#[pallet::constant]
+		type KeepSenderAlive: Get<bool>;
+
+		/// Where the funds of new locks are kept. `Custody::Hold` needs the pallet as the
+		/// `Freezer` of the assets pallet behind `Fungibles`, asset locks fail with
+		/// `AssetsNotFrozen` otherwise. `Custody::SubAccount` escrows every lock in its own
+		/// account.
+		#[pallet::constant]
+		type LockCustody: Get<Custody>;
+
+		/// Maximum number of pending locks an account can have as sender, unless
+		/// `Config::AdminOrigin` sets a different limit for it.
+		#[pallet::constant]
+		type MaxOpenLocksPerAccount: Get<u32>;
+
+		/// Type to access the Assets Pallet. Asset balances share the native balance type so both
+		/// kinds of swaps can be recorded in the same `LockDetails`.
+		type Fungibles: fungibles::Inspect<
+				Self::AccountId,
+				Balance = <Self::Currency as Currency<Self::AccountId>>::Balance,
+			> + fungibles::Mutate<Self::AccountId>
 			+ fungibles::metadata::Mutate<Self::AccountId>
 			+ fungibles::InspectMetadata<Self::AccountId>
 			+ fungibles::Transfer<Self::AccountId>
 			+ fungibles::Create<Self::AccountId>;
+
+		/// Origin allowed to pause and unpause the pallet.
+		type PauseOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Origin allowed to set the swap and fee policies of an asset and the open locks limit
+		/// of an account.
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// When true, only assets with an `AssetPolicies` entry can be locked.
+		#[pallet::constant]
+		type RequireAssetPolicy: Get<bool>;
+
+		/// Base priority of `unlock_unsigned` transactions in the pool, raised as the expiry of the
+		/// lock gets closer.
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
+
+		/// Signature of the recipient authorising a relayer to claim a lock with `unlock_for`.
+		type OffchainSignature: Verify<Signer = Self::OffchainPublic> + Parameter;
+
+		/// Public key of `OffchainSignature`, identifying the recipient's account.
+		type OffchainPublic: IdentifyAccount<AccountId = Self::AccountId> + Clone;
+
+		/// Signs the `unlock_for` claims of the benchmarks.
+		#[cfg(feature = "runtime-benchmarks")]
+		type BenchmarkHelper: BenchmarkHelper<Self::OffchainPublic, Self::OffchainSignature>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	/// asset locked by a swap
+	pub enum SwapAsset<AssetId> {
+		/// native currency of the chain, moved through `Config::Currency`
+		Native,
+		/// asset moved through `Config::Fungibles`
+		Asset(AssetId),
+	}
+
+	impl<AssetId> Default for SwapAsset<AssetId> {
+		fn default() -> Self {
+			SwapAsset::Native
+		}
+	}
+
+	#[derive(
+		Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default, MaxEncodedLen, TypeInfo,
+	)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	/// hash function used to build a hashlock from its preimage
+	pub enum HashAlgorithm {
+		/// SHA-256, used by most HTLC implementations
+		#[default]
+		Sha2_256,
+		/// Keccak-256, used by Ethereum HTLCs
+		Keccak256,
+		/// Blake2-256, native hash of Substrate chains
+		Blake2_256,
+		/// RIPEMD160(SHA-256), used by Bitcoin and Lightning scripts. The 20-byte digest is
+		/// stored right-padded with zeros.
+		Hash160,
+	}
+
+	impl HashAlgorithm {
+		/// hashes the preimage into a 32-byte hashlock
+		pub fn hash(&self, preimage: &[u8]) -> [u8; 32] {
+			match self {
+				HashAlgorithm::Sha2_256 => hashing::sha2_256(preimage),
+				HashAlgorithm::Keccak256 => hashing::keccak_256(preimage),
+				HashAlgorithm::Blake2_256 => hashing::blake2_256(preimage),
+				HashAlgorithm::Hash160 => {
+					let digest = Ripemd160::digest(hashing::sha2_256(preimage));
+					let mut hashlock = [0u8; 32];
+					hashlock[..20].copy_from_slice(digest.as_slice());
+					hashlock
+				},
+			}
+		}
+	}
+
+	#[derive(
+		Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default, MaxEncodedLen, TypeInfo,
+	)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	/// state of a swap, `at` is the block where it left `Pending`
+	pub enum SwapStatus<BlockNumber> {
+		/// funds are held until the recipient unlocks them or the timelock expires
+		#[default]
+		Pending,
+		/// funds were unlocked by the recipient
+		Claimed { at: BlockNumber },
+		/// funds were returned to the sender
+		Refunded { at: BlockNumber },
+	}
+
+	impl<BlockNumber> SwapStatus<BlockNumber> {
+		/// true while the funds are held by the pallet
+		pub fn is_pending(&self) -> bool {
+			matches!(self, SwapStatus::Pending)
+		}
+	}
+
+	#[derive(
+		Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default, MaxEncodedLen, TypeInfo,
+	)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	/// where the funds of a lock are kept until it is claimed or refunded
+	pub enum Custody {
+		/// moved to the pallet account, shared by every lock
+		#[default]
+		PalletAccount,
+		/// kept in the sender's account, reserved for the native currency and frozen for assets
+		Hold,
+		/// moved to an escrow account derived from the pallet account and the lock id, kept
+		/// alive by an existential deposit of native currency paid by the sender
+		SubAccount,
+	}
+
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	/// duration of a new lock
+	pub enum Timelock<BlockNumber, Moment> {
+		/// lock expires this number of blocks after the current one
+		Blocks(BlockNumber),
+		/// lock expires at this timestamp of `Config::Time`
+		Until(Moment),
+	}
+
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	/// point where a lock expires
+	pub enum Expiry<BlockNumber, Moment> {
+		/// lock expires once this block is reached
+		AtBlock(BlockNumber),
+		/// lock expires once `Config::Time` reaches this timestamp
+		AtTime(Moment),
+	}
+
+	impl<BlockNumber: Default, Moment> Default for Expiry<BlockNumber, Moment> {
+		fn default() -> Self {
+			Expiry::AtBlock(Default::default())
+		}
 	}
 
 	#[derive(
 		Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default, MaxEncodedLen, TypeInfo,
 	)]
-	/// structure for saving all lock details
-	pub struct LockDetails<AssetBalance, AssetId, AccountId, BlockNumber> {
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	/// structure for saving all lock details. `expiration_block` is the block where the lock is
+	/// checked for automatic refunds: the expiry itself for block-based locks, an estimate for
+	/// time-based ones.
+	pub struct LockDetails<AssetBalance, AssetId, AccountId, BlockNumber, Moment> {
 		pub tx_id: [u8; 32],
 		pub sender: AccountId,
 		pub recipient: AccountId,
-		pub asset_id: AssetId,
+		pub asset: SwapAsset<AssetId>,
 		pub amount: AssetBalance,
 		pub hashlock: [u8; 32],
+		pub hash_algorithm: HashAlgorithm,
+		pub expiry: Expiry<BlockNumber, Moment>,
 		pub expiration_block: BlockNumber,
-		pub is_withdraw: bool,
-		pub is_refunded: bool,
+		pub status: SwapStatus<BlockNumber>,
+		/// amount reserved from the sender for the storage of the lock and its secret
+		pub deposit: AssetBalance,
+		/// where the funds of the lock are kept
+		pub custody: Custody,
+		/// part of `amount` paid to `Config::FeeDestination` when the lock is claimed
+		pub fee: AssetBalance,
 	}
 
 	/// type for modeling LockDetails
 	pub type LockDetailsOf<T> =
-		LockDetails<AssetBalanceOf<T>, AssetIdOf<T>, AccountIdOf<T>, BlockNumberOf<T>>;
+		LockDetails<AssetBalanceOf<T>, AssetIdOf<T>, AccountIdOf<T>, BlockNumberOf<T>, MomentOf<T>>;
+
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	/// status of a lock as reported to clients
+	pub enum LockStatus {
+		/// waiting for the recipient to unlock it
+		Pending,
+		/// timelock has expired and the sender can cancel it
+		Expired,
+		/// funds were unlocked by the recipient
+		Claimed,
+		/// funds were returned to the sender
+		Refunded,
+	}
+
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default, TypeInfo)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	/// custody balances of an asset as reported to auditors
+	pub struct CustodyAudit<Balance> {
+		/// amount of the pending locks of the asset as counted in `TotalLocked`
+		pub total_locked: Balance,
+		/// sum of the amounts of the pending locks of the asset
+		pub expected: Balance,
+		/// balance of the asset in the accounts and holds keeping the funds of the pending locks
+		pub actual: Balance,
+	}
+
+	impl<Balance: PartialOrd> CustodyAudit<Balance> {
+		/// true when `TotalLocked` matches the pending locks and their custody holds them
+		pub fn is_solvent(&self) -> bool {
+			self.total_locked == self.expected && self.actual >= self.expected
+		}
+	}
+
+	/// type for modeling CustodyAudit
+	pub type CustodyAuditOf<T> = CustodyAudit<AssetBalanceOf<T>>;
+
+	#[derive(
+		Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default, MaxEncodedLen, TypeInfo,
+	)]
+	/// limits applied to the locks of an asset
+	pub struct AssetPolicy<Balance> {
+		/// new locks of the asset are accepted
+		pub enabled: bool,
+		/// minimum amount of a lock
+		pub min_amount: Balance,
+		/// maximum amount of a lock, unlimited when `None`
+		pub max_amount: Option<Balance>,
+		/// maximum amount held by all pending locks of the asset, unlimited when `None`
+		pub tvl_cap: Option<Balance>,
+	}
+
+	/// type for modeling AssetPolicy
+	pub type AssetPolicyOf<T> = AssetPolicy<AssetBalanceOf<T>>;
+
+	#[derive(
+		Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default, MaxEncodedLen, TypeInfo,
+	)]
+	/// fee charged when a lock of an asset is claimed. The fee of a lock is set when it is
+	/// created and can't exceed its amount.
+	pub struct FeePolicy<Balance> {
+		/// part of the amount of the lock
+		pub rate: Permill,
+		/// fixed amount added to the fee
+		pub flat: Balance,
+		/// scales the fee by the duration of the lock, relative to `MaxTimelock`
+		pub scale_by_duration: bool,
+	}
+
+	/// type for modeling FeePolicy
+	pub type FeePolicyOf<T> = FeePolicy<AssetBalanceOf<T>>;
+
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+	/// what is blocked while the pallet is paused
+	pub enum PauseMode {
+		/// new locks are rejected, in-flight swaps can still be unlocked or canceled
+		LocksOnly,
+		/// locks, unlocks, cancels and automatic refunds are rejected
+		All,
+	}
 
 	#[pallet::storage]
 	#[pallet::getter(fn lock_transactions)]
@@ -102,26 +466,245 @@ pub mod pallet {
 	#[pallet::getter(fn known_secrets)]
 	/// Data storage for keeping all lock transactions
 	pub(super) type KnownSecrets<T: Config> =
-		StorageMap<_, Blake2_128Concat, [u8; 32], Vec<u8>, OptionQuery>;
+		StorageMap<_, Blake2_128Concat, [u8; 32], PreimageOf<T>, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn oversized_secrets)]
+	/// Secrets longer than `MaxPreimageLen` revealed before it bounded them, moved out of
+	/// `KnownSecrets` by the v5 migration and removed when their lock is pruned
+	pub(super) type OversizedSecrets<T: Config> =
+		StorageMap<_, Blake2_128Concat, [u8; 32], LegacyPreimageOf<T>, OptionQuery>;
+
+	#[pallet::storage]
+	/// Pending locks indexed by expiration block, used to refund them automatically
+	pub(super) type ExpiringLocks<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		BlockNumberOf<T>,
+		Blake2_128Concat,
+		[u8; 32],
+		(),
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// Pending locks indexed by sender
+	pub(super) type LocksBySender<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		AccountIdOf<T>,
+		Blake2_128Concat,
+		[u8; 32],
+		(),
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// Pending locks indexed by recipient
+	pub(super) type LocksByRecipient<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		AccountIdOf<T>,
+		Blake2_128Concat,
+		[u8; 32],
+		(),
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// Pending locks indexed by hashlock
+	pub(super) type LocksByHashlock<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		[u8; 32],
+		Blake2_128Concat,
+		[u8; 32],
+		(),
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn asset_policies)]
+	/// Swap policy of each asset, set by `Config::AdminOrigin`
+	pub(super) type AssetPolicies<T: Config> =
+		StorageMap<_, Blake2_128Concat, SwapAssetOf<T>, AssetPolicyOf<T>, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn fee_policies)]
+	/// Fee charged on the locks of each asset, set by `Config::AdminOrigin`
+	pub(super) type FeePolicies<T: Config> =
+		StorageMap<_, Blake2_128Concat, SwapAssetOf<T>, FeePolicyOf<T>, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn total_locked)]
+	/// Amount held by the pending locks of each asset
+	pub(super) type TotalLocked<T: Config> =
+		StorageMap<_, Blake2_128Concat, SwapAssetOf<T>, AssetBalanceOf<T>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn held_assets)]
+	/// Amount of each asset frozen in the account of the senders of pending `Custody::Hold` locks
+	pub(super) type HeldAssets<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		AssetIdOf<T>,
+		Blake2_128Concat,
+		AccountIdOf<T>,
+		AssetBalanceOf<T>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn paused)]
+	/// Set while the pallet is paused by `Config::PauseOrigin`
+	pub(super) type Paused<T: Config> = StorageValue<_, PauseMode, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn lock_nonces)]
+	/// Number of locks with a derived id created by each account
+	pub(super) type LockNonces<T: Config> =
+		StorageMap<_, Blake2_128Concat, AccountIdOf<T>, u64, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn open_lock_count)]
+	/// Number of pending locks sent by each account
+	pub(super) type OpenLockCount<T: Config> =
+		StorageMap<_, Blake2_128Concat, AccountIdOf<T>, u32, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn open_lock_limits)]
+	/// Open locks limit of the accounts allowed more, or fewer, than `MaxOpenLocksPerAccount`,
+	/// set by `Config::AdminOrigin`
+	pub(super) type OpenLockLimits<T: Config> =
+		StorageMap<_, Blake2_128Concat, AccountIdOf<T>, u32, OptionQuery>;
+
+	#[pallet::storage]
+	/// Next expiration block to be processed by the automatic refunds
+	pub(super) type ExpiryCursor<T: Config> = StorageValue<_, BlockNumberOf<T>, ValueQuery>;
+
+	#[pallet::storage]
+	/// Claimed and refunded locks indexed by the block where they were settled, used to prune
+	/// them once `RetentionPeriod` has elapsed
+	pub(super) type SettledLocks<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		BlockNumberOf<T>,
+		Blake2_128Concat,
+		[u8; 32],
+		(),
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	/// Next settlement block to be processed by the pruning
+	pub(super) type PruneCursor<T: Config> = StorageValue<_, BlockNumberOf<T>, ValueQuery>;
+
+	#[pallet::storage]
+	/// Ids of pruned locks, kept so they can't be used again
+	pub(super) type PrunedTxIds<T: Config> =
+		StorageMap<_, Blake2_128Concat, [u8; 32], (), OptionQuery>;
 
 	// Pallets use events to inform users when important changes are made.
 	// https://docs.substrate.io/main-docs/build/events-errors/
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		/// Notify about new lock transaction
+		/// Notify about new lock transaction. Lock events are indexed by hashlock and tx_id.
 		Locked {
 			tx_id: [u8; 32],
+			sender: AccountIdOf<T>,
 			recipient: AccountIdOf<T>,
 			hashlock: [u8; 32],
-			expiration_block: BlockNumberOf<T>,
-			asset_id: AssetIdOf<T>,
+			hash_algorithm: HashAlgorithm,
+			expiry: ExpiryOf<T>,
+			asset: SwapAssetOf<T>,
 			asset_amount: AssetBalanceOf<T>,
+			deposit: BalanceOf<T>,
+		},
+		/// Notify about unlock transaction, revealing the secret of the hashlock
+		Unlocked {
+			tx_id: [u8; 32],
+			sender: AccountIdOf<T>,
+			recipient: AccountIdOf<T>,
+			asset: SwapAssetOf<T>,
+			asset_amount: AssetBalanceOf<T>,
+			fee: AssetBalanceOf<T>,
+			preimage: PreimageOf<T>,
 		},
-		/// Notify about unlock transaction
-		Unlocked { tx_id: [u8; 32] },
 		/// Notify about canceled transaction
-		Canceled { tx_id: [u8; 32] },
+		Canceled {
+			tx_id: [u8; 32],
+			sender: AccountIdOf<T>,
+			recipient: AccountIdOf<T>,
+			asset: SwapAssetOf<T>,
+			asset_amount: AssetBalanceOf<T>,
+		},
+		/// Notify about expired transaction refunded to its sender by the pallet
+		AutoRefunded {
+			tx_id: [u8; 32],
+			sender: AccountIdOf<T>,
+			recipient: AccountIdOf<T>,
+			asset: SwapAssetOf<T>,
+			asset_amount: AssetBalanceOf<T>,
+		},
+		/// Notify about the pallet being paused
+		Paused { mode: PauseMode },
+		/// Notify about the pallet being unpaused
+		Unpaused,
+		/// Notify about the swap policy of an asset being set
+		AssetPolicySet { asset: SwapAssetOf<T>, policy: AssetPolicyOf<T> },
+		/// Notify about the swap policy of an asset being removed
+		AssetPolicyRemoved { asset: SwapAssetOf<T> },
+		/// Notify about a settled lock and its secret being removed from storage
+		Pruned { tx_id: [u8; 32] },
+		/// Notify about part of the storage deposit of a lock being returned to its sender
+		DepositReleased { tx_id: [u8; 32], who: AccountIdOf<T>, amount: BalanceOf<T> },
+		/// Notify about the open locks limit of an account being set, or reset to
+		/// `MaxOpenLocksPerAccount` when `None`
+		OpenLockLimitSet { who: AccountIdOf<T>, limit: Option<u32> },
+		/// Notify about the fee policy of an asset being set, or removed when `None`
+		FeePolicySet { asset: SwapAssetOf<T>, policy: Option<FeePolicyOf<T>> },
+		/// Notify about the relayer of an `unlock_for` being paid out of the claimed lock
+		RelayerPaid { tx_id: [u8; 32], relayer: AccountIdOf<T>, fee: AssetBalanceOf<T> },
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+			if T::ensure_settlements_allowed().is_err() {
+				return T::DbWeight::get().reads(1)
+			}
+			Self::refund_expired(now, T::MaxAutoRefundsPerBlock::get())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn try_state(_: BlockNumberFor<T>) -> Result<(), &'static str> {
+			Self::do_try_state()
+		}
+
+		fn on_idle(now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			let mut used = T::DbWeight::get().reads(1);
+			if T::ensure_settlements_allowed().is_ok() {
+				let per_refund = T::WeightInfo::refund_expired(1).ref_time().max(1);
+				let max_refunds = remaining_weight.saturating_sub(used).ref_time() / per_refund;
+				if !max_refunds.is_zero() {
+					used = used.saturating_add(Self::refund_expired(
+						now,
+						max_refunds.min(u32::MAX as u64) as u32,
+					));
+				}
+			}
+			// settled locks are pruned with the weight left by the refunds.
+			let per_prune = T::WeightInfo::prune_settled(1).ref_time().max(1);
+			let max_prunes = remaining_weight.saturating_sub(used).ref_time() / per_prune;
+			if !max_prunes.is_zero() {
+				used = used.saturating_add(Self::prune_settled(
+					now,
+					max_prunes.min(u32::MAX as u64) as u32,
+				));
+			}
+			used
+		}
 	}
 
 	// Errors inform users that something went wrong.
@@ -155,6 +738,60 @@ pub mod pallet {
 		InvalidReceiver,
 		/// Timelock has not expired
 		TimeLockNotExpired,
+		/// preimage must be exactly 32 bytes long
+		InvalidSecretLength,
+		/// preimage is longer than `MaxPreimageLen`
+		PreimageTooLong,
+		/// pallet is paused
+		PalletPaused,
+		/// pallet is not paused
+		NotPaused,
+		/// asset has no swap policy and `RequireAssetPolicy` is set
+		AssetNotAllowed,
+		/// locks of the asset are disabled by its swap policy
+		AssetDisabled,
+		/// amount is below the minimum of the asset's swap policy
+		AmountBelowMinimum,
+		/// amount is above the maximum of the asset's swap policy
+		AmountAboveMaximum,
+		/// lock would exceed the total value locked cap of the asset's swap policy
+		TvlCapExceeded,
+		/// asset has no swap policy
+		AssetPolicyNotFound,
+		/// timelock is shorter than `MinTimelock`
+		TimelockTooShort,
+		/// timelock is longer than `MaxTimelock`
+		TimelockTooLong,
+		/// lock must expire at least `MinTimelockMargin` blocks before the pending lock it answers
+		TimelockMarginTooShort,
+		/// `tx_id` must be empty because the pallet derives lock ids
+		TransactionIdNotAllowed,
+		/// `tx_id` must be provided because the pallet doesn't derive lock ids
+		TransactionIdRequired,
+		/// lock is still pending and can't be pruned
+		SwapPending,
+		/// lock was settled less than `RetentionPeriod` blocks ago
+		RetentionNotElapsed,
+		/// sender can't pay the storage deposit of the lock, and the existential deposit of its
+		/// escrow, on top of its amount
+		InsufficientDeposit,
+		/// sender has reached its limit of pending locks
+		TooManyOpenLocks,
+		/// funds are frozen or locked and can't be moved
+		BalanceFrozen,
+		/// lock would take the sender below its minimum balance while `KeepSenderAlive` is set
+		WouldReapSender,
+		/// recipient would hold less than the minimum balance of the asset
+		RecipientBelowMinimum,
+		/// recipient has no account and can't be given one for the asset
+		RecipientCannotCreate,
+		/// signature doesn't authorise the claim for the recipient of the lock
+		InvalidSignature,
+		/// relayer and protocol fees exceed the amount of the lock
+		RelayerFeeTooHigh,
+		/// `Custody::Hold` can't freeze the asset, the pallet isn't the `Freezer` of the assets
+		/// pallet
+		AssetsNotFrozen,
 	}
 
 	impl<T: Config> Pallet<T> {
@@ -162,48 +799,823 @@ pub mod pallet {
 		pub fn account_id() -> T::AccountId {
 			T::PalletId::get().into_account_truncating()
 		}
+
+		/// moves `amount` of `asset` between two accounts, keeping the source alive when
+		/// `keep_alive` is set
+		pub(crate) fn transfer_asset(
+			asset: SwapAssetOf<T>,
+			source: &T::AccountId,
+			dest: &T::AccountId,
+			amount: AssetBalanceOf<T>,
+			keep_alive: bool,
+		) -> DispatchResult {
+			match asset {
+				SwapAsset::Native => {
+					let existence = if keep_alive {
+						ExistenceRequirement::KeepAlive
+					} else {
+						ExistenceRequirement::AllowDeath
+					};
+					T::Currency::transfer(source, dest, amount, existence)
+				},
+				SwapAsset::Asset(asset_id) =>
+					T::Fungibles::transfer(asset_id, source, dest, amount, keep_alive).map(|_| ()),
+			}
+		}
+
+		/// weight of `ensure_timelock_margin` reading the pending locks of `recipient`, on top of
+		/// the lock weights
+		pub(crate) fn timelock_margin_weight(recipient: &AccountIdOf<T>) -> Weight {
+			if T::MinTimelockMargin::get().is_zero() {
+				return Weight::zero()
+			}
+			let open_locks = OpenLockCount::<T>::get(recipient) as u64;
+			T::DbWeight::get().reads(1 + 2 * open_locks)
+		}
+
+		/// escrow account of the lock `tx_id` when its custody is `Custody::SubAccount`. The id is
+		/// hashed to 16 bytes so all of it fits in a 32-byte account after the `PalletId`, and
+		/// ids sharing a prefix don't share an escrow.
+		pub fn escrow_account(tx_id: [u8; 32]) -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating(hashing::blake2_128(&tx_id))
+		}
+
+		/// account holding the funds of a lock, `None` when they stay in the sender's account
+		pub fn custodian(lock_details: &LockDetailsOf<T>) -> Option<T::AccountId> {
+			match lock_details.custody {
+				Custody::PalletAccount => Some(Self::account_id()),
+				Custody::Hold => None,
+				Custody::SubAccount => Some(Self::escrow_account(lock_details.tx_id)),
+			}
+		}
+
+		/// identifier of the named reserve holding the native currency of `Custody::Hold` locks
+		pub fn hold_id() -> [u8; 8] {
+			T::PalletId::get().0
+		}
+
+		/// takes the funds of a new lock from its sender into the custody of the lock
+		pub(crate) fn hold_funds(lock_details: &LockDetailsOf<T>) -> DispatchResult {
+			let (asset, sender, amount) =
+				(lock_details.asset, &lock_details.sender, lock_details.amount);
+			let keep_alive = T::KeepSenderAlive::get();
+			match (lock_details.custody, asset) {
+				(Custody::PalletAccount, _) =>
+					Self::transfer_asset(asset, sender, &Self::account_id(), amount, keep_alive),
+				(Custody::Hold, SwapAsset::Native) =>
+					T::Currency::reserve_named(&Self::hold_id(), sender, amount),
+				(Custody::Hold, SwapAsset::Asset(asset_id)) => {
+					let reducible = || T::Fungibles::reducible_balance(asset_id, sender, false);
+					let before = reducible();
+					ensure!(before >= amount, Error::<T>::LowBalance);
+					<HeldAssets<T>>::try_mutate(asset_id, sender, |held| -> DispatchResult {
+						*held = held.checked_add(&amount).ok_or(Error::<T>::OverflowOrUnderflow)?;
+						Ok(())
+					})?;
+					// `HeldAssets` only freezes the funds if the assets pallet asks the pallet for
+					// them, as its `Freezer`.
+					ensure!(reducible() < before, Error::<T>::AssetsNotFrozen);
+					Ok(())
+				},
+				(Custody::SubAccount, _) => {
+					let escrow = Self::escrow_account(lock_details.tx_id);
+					// the native existential deposit keeps the escrow account alive, and provides
+					// for it when the asset is not sufficient.
+					Self::transfer_asset(
+						SwapAsset::Native,
+						sender,
+						&escrow,
+						T::Currency::minimum_balance(),
+						keep_alive,
+					)?;
+					Self::transfer_asset(asset, sender, &escrow, amount, keep_alive)
+				},
+			}
+		}
+
+		/// pays the funds of a lock out of its custody: each of `fees` to its account and the rest
+		/// to `dest`, the sender or the recipient
+		pub(crate) fn release_funds(
+			lock_details: &LockDetailsOf<T>,
+			dest: &AccountIdOf<T>,
+			fees: &[(AccountIdOf<T>, AssetBalanceOf<T>)],
+		) -> DispatchResult {
+			let (asset, sender, amount) =
+				(lock_details.asset, &lock_details.sender, lock_details.amount);
+			let paid = fees
+				.iter()
+				.fold(Zero::zero(), |paid: AssetBalanceOf<T>, (_, fee)| paid.saturating_add(*fee));
+			let payout = [(dest.clone(), amount.saturating_sub(paid))];
+			let payouts = payout.iter().chain(fees).filter(|(_, value)| !value.is_zero());
+			match (lock_details.custody, asset) {
+				(Custody::PalletAccount, _) => {
+					for (to, value) in payouts {
+						Self::transfer_asset(asset, &Self::account_id(), to, *value, true)?;
+					}
+					Ok(())
+				},
+				(Custody::Hold, SwapAsset::Native) => {
+					for (to, value) in payouts {
+						let missing = T::Currency::repatriate_reserved_named(
+							&Self::hold_id(),
+							sender,
+							to,
+							*value,
+							BalanceStatus::Free,
+						)?;
+						ensure!(missing.is_zero(), Error::<T>::LowBalance);
+					}
+					Ok(())
+				},
+				(Custody::Hold, SwapAsset::Asset(asset_id)) => {
+					<HeldAssets<T>>::mutate_exists(asset_id, sender, |held| {
+						*held = held
+							.map(|held| held.saturating_sub(amount))
+							.filter(|held| !held.is_zero())
+					});
+					for (to, value) in payouts.filter(|(to, _)| to != sender) {
+						T::Fungibles::transfer(asset_id, sender, to, *value, false)?;
+					}
+					Ok(())
+				},
+				(Custody::SubAccount, _) => {
+					let escrow = Self::escrow_account(lock_details.tx_id);
+					for (to, value) in payouts {
+						Self::transfer_asset(asset, &escrow, to, *value, false)?;
+					}
+					// reaps the escrow account, returning its existential deposit to the sender.
+					// Anyone can keep the escrow alive with a consumer, e.g. an account of another
+					// asset, so the deposit is returned on a best effort basis and only what is
+					// above it is refunded then.
+					let balance = T::Currency::free_balance(&escrow);
+					if Self::transfer_asset(SwapAsset::Native, &escrow, sender, balance, false)
+						.is_err()
+					{
+						let surplus = balance.saturating_sub(T::Currency::minimum_balance());
+						if !surplus.is_zero() {
+							let _ = Self::transfer_asset(
+								SwapAsset::Native,
+								&escrow,
+								sender,
+								surplus,
+								true,
+							);
+						}
+					}
+					Ok(())
+				},
+			}
+		}
+
+		/// fee charged on unlock for a lock of `amount` of `asset` lasting `duration` blocks,
+		/// following the fee policy of the asset
+		pub fn fee_for(
+			asset: SwapAssetOf<T>,
+			amount: AssetBalanceOf<T>,
+			duration: BlockNumberOf<T>,
+		) -> AssetBalanceOf<T> {
+			let policy = match FeePolicies::<T>::get(asset) {
+				Some(policy) => policy,
+				None => return Zero::zero(),
+			};
+			let mut fee = policy.flat.saturating_add(policy.rate * amount);
+			if policy.scale_by_duration {
+				let max_timelock = T::MaxTimelock::get().saturated_into::<u64>().max(1);
+				let duration = duration.saturated_into::<u64>().min(max_timelock);
+				fee = Perbill::from_rational(duration, max_timelock) * fee;
+			}
+			fee.min(amount)
+		}
+
+		/// validates and stores a new lock, taking the funds into its custody
+		// takes the arguments of `lock` as they are, grouping them would only move the list
+		#[allow(clippy::too_many_arguments)]
+		pub(crate) fn do_lock(
+			who: T::AccountId,
+			tx_id: Option<[u8; 32]>,
+			recipient: AccountIdOf<T>,
+			hashlock: [u8; 32],
+			hash_algorithm: HashAlgorithm,
+			timelock: TimelockOf<T>,
+			asset: SwapAssetOf<T>,
+			asset_amount: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			T::ensure_locks_allowed()?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			let expiry = match timelock {
+				Timelock::Blocks(blocks) => Expiry::AtBlock(now.saturating_add(blocks)),
+				Timelock::Until(moment) => Expiry::AtTime(moment),
+			};
+			let tx_id = match (tx_id, T::DeriveTxIds::get()) {
+				(None, true) => {
+					let tx_id = Self::derive_tx_id(
+						&who,
+						&recipient,
+						hashlock,
+						asset,
+						asset_amount,
+						&expiry,
+					);
+					<LockNonces<T>>::mutate(&who, |nonce| *nonce = nonce.saturating_add(1));
+					tx_id
+				},
+				(Some(tx_id), false) => tx_id,
+				(Some(_), true) => return Err(Error::<T>::TransactionIdNotAllowed.into()),
+				(None, false) => return Err(Error::<T>::TransactionIdRequired.into()),
+			};
+			ensure!(T::lock_details_exists(tx_id) == false, Error::<T>::TransactionIdExists);
+			T::ensure_can_open_lock(&who)?;
+			T::ensure_valid_deadline(&expiry)?;
+			let expiration_block = Self::expected_block(&expiry);
+			T::ensure_timelock_margin(&who, &recipient, hashlock, &expiration_block)?;
+			T::ensure_is_not_zero(asset_amount)?;
+			T::ensure_asset_exists(asset)?;
+			T::ensure_policy_allows(asset, asset_amount)?;
+			T::ensure_has_balance(&who, asset, asset_amount, T::KeepSenderAlive::get())?;
+			let custody = T::LockCustody::get();
+			let deposit = Self::deposit_for(PreimageOf::<T>::max_encoded_len());
+			// the storage deposit, and the existential deposit of a `Custody::SubAccount` escrow,
+			// are paid in the native currency on top of the amount of a native lock
+			let escrow_deposit = match custody {
+				Custody::SubAccount => T::Currency::minimum_balance(),
+				Custody::PalletAccount | Custody::Hold => Zero::zero(),
+			};
+			let native_costs = deposit.saturating_add(escrow_deposit);
+			if !native_costs.is_zero() {
+				let native_amount = match asset {
+					SwapAsset::Native => asset_amount.saturating_add(native_costs),
+					SwapAsset::Asset(_) => native_costs,
+				};
+				T::ensure_has_balance(&who, SwapAsset::Native, native_amount, false).map_err(
+					|error| match error {
+						Error::LowBalance => Error::<T>::InsufficientDeposit,
+						error => error,
+					},
+				)?;
+			}
+			let mut fee = Self::fee_for(asset, asset_amount, expiration_block.saturating_sub(now));
+			// a fee `FeeDestination` can't receive, e.g. below the minimum balance of an account
+			// it doesn't have yet, is left to the recipient instead of failing the lock.
+			if !fee.is_zero() &&
+				T::ensure_can_receive(&T::FeeDestination::get(), asset, fee).is_err()
+			{
+				fee = Zero::zero();
+			}
+			T::ensure_can_receive(&recipient, asset, asset_amount.saturating_sub(fee))?;
+			T::Currency::reserve(&who, deposit).map_err(|_| Error::<T>::InsufficientDeposit)?;
+			let lock_details = LockDetails {
+				tx_id,
+				sender: who.clone(),
+				recipient: recipient.clone(),
+				asset,
+				amount: asset_amount,
+				hashlock,
+				hash_algorithm,
+				expiry,
+				expiration_block,
+				status: SwapStatus::Pending,
+				deposit,
+				custody,
+				fee,
+			};
+			Self::hold_funds(&lock_details)?;
+			Self::insert_indexes(&lock_details);
+			<TotalLocked<T>>::mutate(asset, |total| *total = total.saturating_add(asset_amount));
+			<LockTransactions<T>>::insert(tx_id, lock_details);
+
+			Self::deposit_lock_event(
+				tx_id,
+				hashlock,
+				Event::Locked {
+					tx_id,
+					sender: who,
+					recipient,
+					hashlock,
+					hash_algorithm,
+					expiry,
+					asset,
+					asset_amount,
+					deposit,
+				},
+			);
+
+			Ok(())
+		}
+
+		/// id of the next lock created by `sender` with these parameters when `DeriveTxIds` is set.
+		/// The nonce of the sender makes every id unique.
+		pub fn derive_tx_id(
+			sender: &AccountIdOf<T>,
+			recipient: &AccountIdOf<T>,
+			hashlock: [u8; 32],
+			asset: SwapAssetOf<T>,
+			amount: AssetBalanceOf<T>,
+			expiry: &ExpiryOf<T>,
+		) -> [u8; 32] {
+			let nonce = <LockNonces<T>>::get(sender);
+			(b"aswap/tx", sender, recipient, hashlock, asset, amount, expiry, nonce)
+				.using_encoded(hashing::blake2_256)
+		}
+
+		/// deposits an event about the lock `tx_id` with its hashlock and tx_id as topics, so
+		/// watchers can follow a swap, and learn its secret, from events alone
+		fn deposit_lock_event(tx_id: [u8; 32], hashlock: [u8; 32], event: Event<T>) {
+			let topics = [Self::topic(hashlock), Self::topic(tx_id)];
+			let event: <T as Config>::RuntimeEvent = event.into();
+			<frame_system::Pallet<T>>::deposit_event_indexed(&topics, event.into());
+		}
+
+		/// event topic for a 32 bytes value: the value itself when `T::Hash` is 32 bytes long,
+		/// its hash otherwise
+		pub(crate) fn topic(value: [u8; 32]) -> T::Hash {
+			T::Hash::decode(&mut &value[..]).unwrap_or_else(|_| T::Hashing::hash(&value))
+		}
+
+		/// claims a lock for its recipient revealing `preimage`, paying `relayer` its fee out of
+		/// the lock when it is claimed by `unlock_for`
+		pub(crate) fn do_unlock(
+			tx_id: [u8; 32],
+			preimage: Vec<u8>,
+			relayer: Option<(AccountIdOf<T>, AssetBalanceOf<T>)>,
+		) -> DispatchResult {
+			let preimage: PreimageOf<T> =
+				preimage.try_into().map_err(|_| Error::<T>::PreimageTooLong)?;
+			T::ensure_hashlock_matches(tx_id, preimage.to_vec())?;
+			T::ensure_withdrawable(tx_id)?;
+			let mut lock_details =
+				LockTransactions::<T>::get(tx_id).ok_or(Error::<T>::TransactionNotExists)?;
+			let mut fees = Vec::from([(T::FeeDestination::get(), lock_details.fee)]);
+			if let Some((relayer, relayer_fee)) = relayer.clone() {
+				ensure!(
+					lock_details.fee.saturating_add(relayer_fee) <= lock_details.amount,
+					Error::<T>::RelayerFeeTooHigh
+				);
+				fees.push((relayer, relayer_fee));
+			}
+			if let Some(custodian) = Self::custodian(&lock_details) {
+				// the pallet account stays alive, escrow accounts are reaped.
+				let keep_alive = custodian == Self::account_id();
+				T::ensure_has_balance(
+					&custodian,
+					lock_details.asset,
+					lock_details.amount,
+					keep_alive,
+				)?;
+			}
+			let now = <frame_system::Pallet<T>>::block_number();
+			lock_details.status = SwapStatus::Claimed { at: now };
+			Self::release_funds(&lock_details, &lock_details.recipient, &fees)?;
+			Self::remove_indexes(&lock_details);
+			Self::release_total_locked(&lock_details);
+			Self::settle_deposit(&mut lock_details, preimage.encoded_size());
+			<SettledLocks<T>>::insert(now, tx_id, ());
+			<LockTransactions<T>>::insert(tx_id, lock_details.clone());
+			<KnownSecrets<T>>::insert(tx_id, preimage.clone());
+			Self::deposit_lock_event(
+				tx_id,
+				lock_details.hashlock,
+				Event::Unlocked {
+					tx_id,
+					sender: lock_details.sender,
+					recipient: lock_details.recipient,
+					asset: lock_details.asset,
+					asset_amount: lock_details.amount,
+					fee: lock_details.fee,
+					preimage,
+				},
+			);
+			if let Some((relayer, fee)) = relayer {
+				Self::deposit_lock_event(
+					tx_id,
+					lock_details.hashlock,
+					Event::RelayerPaid { tx_id, relayer, fee },
+				);
+			}
+			Ok(())
+		}
+
+		/// message signed by the recipient of the lock `tx_id` to let a relayer claim it with
+		/// `unlock_for` for `relayer_fee`, tagged so it can't be mistaken for another protocol's
+		pub fn unlock_for_payload(tx_id: [u8; 32], relayer_fee: AssetBalanceOf<T>) -> Vec<u8> {
+			let genesis_hash = <frame_system::Pallet<T>>::block_hash(T::BlockNumber::zero());
+			(b"aswap/unlock_for", tx_id, relayer_fee, genesis_hash).encode()
+		}
+
+		/// returns the funds of an expired lock to its sender, returning the refunded lock
+		pub(crate) fn do_refund(tx_id: [u8; 32]) -> Result<LockDetailsOf<T>, DispatchError> {
+			T::ensure_refundable(tx_id)?;
+			let mut lock_details =
+				LockTransactions::<T>::get(tx_id).ok_or(Error::<T>::TransactionNotExists)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			lock_details.status = SwapStatus::Refunded { at: now };
+			Self::release_funds(&lock_details, &lock_details.sender, &[])?;
+			Self::remove_indexes(&lock_details);
+			Self::release_total_locked(&lock_details);
+			Self::settle_deposit(&mut lock_details, 0);
+			<SettledLocks<T>>::insert(now, tx_id, ());
+			<LockTransactions<T>>::insert(tx_id, lock_details.clone());
+			Ok(lock_details)
+		}
+
+		/// amount of the pending locks of `asset` compared with the balance of the accounts and
+		/// holds keeping them. Reads every lock, so it is meant for runtime APIs and `try_state`.
+		pub fn audit(asset: SwapAssetOf<T>) -> CustodyAuditOf<T> {
+			let mut expected = AssetBalanceOf::<T>::zero();
+			let mut custodians = BTreeSet::from([Self::account_id()]);
+			let mut holders = BTreeSet::new();
+			for lock_details in LockTransactions::<T>::iter_values() {
+				if lock_details.asset != asset || !lock_details.status.is_pending() {
+					continue
+				}
+				expected = expected.saturating_add(lock_details.amount);
+				match Self::custodian(&lock_details) {
+					Some(custodian) => custodians.insert(custodian),
+					None => holders.insert(lock_details.sender),
+				};
+			}
+			let mut actual = AssetBalanceOf::<T>::zero();
+			for custodian in custodians {
+				actual = actual.saturating_add(match asset {
+					SwapAsset::Native => T::Currency::free_balance(&custodian),
+					SwapAsset::Asset(asset_id) => T::Fungibles::balance(asset_id, &custodian),
+				});
+			}
+			for holder in holders {
+				actual = actual.saturating_add(match asset {
+					SwapAsset::Native =>
+						T::Currency::reserved_balance_named(&Self::hold_id(), &holder),
+					// the bookkeeping of `HeldAssets` only counts as far as the holder still has
+					// the funds
+					SwapAsset::Asset(asset_id) => HeldAssets::<T>::get(asset_id, &holder)
+						.min(T::Fungibles::balance(asset_id, &holder)),
+				});
+			}
+			CustodyAudit { total_locked: TotalLocked::<T>::get(asset), expected, actual }
+		}
+
+		/// checks that every asset is solvent and that the indexes and counters match the
+		/// pending and settled locks
+		#[cfg(any(feature = "try-runtime", test))]
+		pub(crate) fn do_try_state() -> Result<(), &'static str> {
+			let mut assets: Vec<SwapAssetOf<T>> = TotalLocked::<T>::iter_keys().collect();
+			let mut open_locks: sp_std::collections::btree_map::BTreeMap<AccountIdOf<T>, u32> =
+				Default::default();
+			let mut pending = 0usize;
+			for (tx_id, lock_details) in LockTransactions::<T>::iter() {
+				ensure!(lock_details.tx_id == tx_id, "aswap: lock stored under another tx_id");
+				if !assets.contains(&lock_details.asset) {
+					assets.push(lock_details.asset);
+				}
+				match lock_details.status {
+					SwapStatus::Pending => {
+						pending += 1;
+						*open_locks.entry(lock_details.sender.clone()).or_default() += 1;
+						let indexed = LocksBySender::<T>::contains_key(&lock_details.sender, tx_id) &&
+							LocksByRecipient::<T>::contains_key(&lock_details.recipient, tx_id) &&
+							LocksByHashlock::<T>::contains_key(lock_details.hashlock, tx_id);
+						ensure!(indexed, "aswap: pending lock missing from an index");
+					},
+					SwapStatus::Claimed { at } | SwapStatus::Refunded { at } => ensure!(
+						SettledLocks::<T>::contains_key(at, tx_id),
+						"aswap: settled lock missing from SettledLocks"
+					),
+				}
+			}
+			// indexes only hold pending locks. A lock that couldn't be refunded automatically
+			// leaves the expiry index.
+			ensure!(
+				LocksBySender::<T>::iter_keys().count() == pending &&
+					LocksByRecipient::<T>::iter_keys().count() == pending &&
+					LocksByHashlock::<T>::iter_keys().count() == pending,
+				"aswap: index holds a lock that is not pending"
+			);
+			for (_, tx_id) in ExpiringLocks::<T>::iter_keys() {
+				ensure!(
+					LockTransactions::<T>::get(tx_id)
+						.map_or(false, |lock_details| lock_details.status.is_pending()),
+					"aswap: expiry index holds a lock that is not pending"
+				);
+			}
+			ensure!(
+				OpenLockCount::<T>::iter().all(|(who, count)| open_locks.get(&who) == Some(&count)) &&
+					OpenLockCount::<T>::iter_keys().count() == open_locks.len(),
+				"aswap: OpenLockCount doesn't match pending locks"
+			);
+			for asset in assets {
+				ensure!(Self::audit(asset).is_solvent(), "aswap: asset is not solvent");
+			}
+			Ok(())
+		}
+
+		/// status of the lock `tx_id`, taking its timelock into account
+		pub fn lock_status(tx_id: [u8; 32]) -> Option<LockStatus> {
+			LockTransactions::<T>::get(tx_id).map(|lock_details| match lock_details.status {
+				SwapStatus::Claimed { .. } => LockStatus::Claimed,
+				SwapStatus::Refunded { .. } => LockStatus::Refunded,
+				SwapStatus::Pending if T::ensure_expired(&lock_details.expiry).is_ok() =>
+					LockStatus::Expired,
+				SwapStatus::Pending => LockStatus::Pending,
+			})
+		}
+
+		/// pending locks sent or received by `account`, ordered by tx_id and starting after
+		/// `cursor`. Reads every pending lock of the account, so it is meant for runtime APIs.
+		pub fn locks_for_account(
+			account: &AccountIdOf<T>,
+			cursor: Option<[u8; 32]>,
+			limit: u32,
+		) -> Vec<LockDetailsOf<T>> {
+			let tx_ids: BTreeSet<[u8; 32]> = LocksBySender::<T>::iter_key_prefix(account)
+				.chain(LocksByRecipient::<T>::iter_key_prefix(account))
+				.filter(|tx_id| cursor.map_or(true, |cursor| *tx_id > cursor))
+				.collect();
+			tx_ids
+				.into_iter()
+				.take(limit as usize)
+				.filter_map(LockTransactions::<T>::get)
+				.collect()
+		}
+
+		/// open locks limit of `who`, `MaxOpenLocksPerAccount` unless set by `set_open_lock_limit`
+		pub fn open_lock_limit(who: &AccountIdOf<T>) -> u32 {
+			OpenLockLimits::<T>::get(who).unwrap_or_else(T::MaxOpenLocksPerAccount::get)
+		}
+
+		/// adds a pending lock to the expiry, sender, recipient and hashlock indexes and to the
+		/// open locks of its sender
+		pub(crate) fn insert_indexes(lock_details: &LockDetailsOf<T>) {
+			let tx_id = lock_details.tx_id;
+			<ExpiringLocks<T>>::insert(lock_details.expiration_block, tx_id, ());
+			<LocksBySender<T>>::insert(&lock_details.sender, tx_id, ());
+			<OpenLockCount<T>>::mutate(&lock_details.sender, |count| {
+				*count = count.saturating_add(1)
+			});
+			<LocksByRecipient<T>>::insert(&lock_details.recipient, tx_id, ());
+			<LocksByHashlock<T>>::insert(lock_details.hashlock, tx_id, ());
+		}
+
+		/// removes a lock that is no longer pending from every index and from the open locks of
+		/// its sender
+		pub(crate) fn remove_indexes(lock_details: &LockDetailsOf<T>) {
+			let tx_id = lock_details.tx_id;
+			<ExpiringLocks<T>>::remove(lock_details.expiration_block, tx_id);
+			<LocksBySender<T>>::remove(&lock_details.sender, tx_id);
+			<OpenLockCount<T>>::mutate_exists(&lock_details.sender, |count| {
+				*count = count.map(|count| count.saturating_sub(1)).filter(|count| !count.is_zero())
+			});
+			<LocksByRecipient<T>>::remove(&lock_details.recipient, tx_id);
+			<LocksByHashlock<T>>::remove(lock_details.hashlock, tx_id);
+		}
+
+		/// whether the current block or time has reached `expiry`
+		pub(crate) fn is_reached(expiry: &ExpiryOf<T>) -> bool {
+			match expiry {
+				Expiry::AtBlock(block) => *block <= <frame_system::Pallet<T>>::block_number(),
+				Expiry::AtTime(moment) => *moment <= T::Time::now(),
+			}
+		}
+
+		/// block where `expiry` is expected to be reached. Time-based expiries are estimated with
+		/// `ExpectedBlockTime`, rounding up so the lock is not checked before it expires.
+		pub(crate) fn expected_block(expiry: &ExpiryOf<T>) -> BlockNumberOf<T> {
+			match expiry {
+				Expiry::AtBlock(block) => *block,
+				Expiry::AtTime(moment) => {
+					let remaining = moment.saturating_sub(T::Time::now());
+					let block_time = T::ExpectedBlockTime::get().max(One::one());
+					let mut blocks = remaining / block_time;
+					if !(remaining % block_time).is_zero() {
+						blocks = blocks.saturating_add(One::one());
+					}
+					<frame_system::Pallet<T>>::block_number()
+						.saturating_add(blocks.saturated_into::<u64>().saturated_into())
+				},
+			}
+		}
+
+		/// indexes a pending time-based lock again at a later block
+		fn reschedule(tx_id: [u8; 32], now: BlockNumberOf<T>) {
+			if let Some(mut lock_details) = LockTransactions::<T>::get(tx_id) {
+				lock_details.expiration_block =
+					Self::expected_block(&lock_details.expiry).max(now.saturating_add(One::one()));
+				<ExpiringLocks<T>>::insert(lock_details.expiration_block, tx_id, ());
+				<LockTransactions<T>>::insert(tx_id, lock_details);
+			}
+		}
+
+		/// deposit for a lock and a secret of `secret_len` encoded bytes
+		pub(crate) fn deposit_for(secret_len: usize) -> BalanceOf<T> {
+			let bytes = LockDetailsOf::<T>::max_encoded_len().saturating_add(secret_len);
+			T::DepositPerByte::get()
+				.saturating_mul((bytes as u32).into())
+				.saturating_add(T::DepositBase::get())
+		}
+
+		/// returns the part of the deposit of a lock that was settled with a secret of
+		/// `secret_len` encoded bytes that its storage no longer needs
+		fn settle_deposit(lock_details: &mut LockDetailsOf<T>, secret_len: usize) {
+			let held = Self::deposit_for(secret_len).min(lock_details.deposit);
+			let excess = lock_details.deposit.saturating_sub(held);
+			lock_details.deposit = held;
+			Self::release_deposit(lock_details.tx_id, &lock_details.sender, excess);
+		}
+
+		/// unreserves `amount` of the deposit of the lock `tx_id` from its sender
+		fn release_deposit(tx_id: [u8; 32], who: &AccountIdOf<T>, amount: BalanceOf<T>) {
+			if amount.is_zero() {
+				return
+			}
+			T::Currency::unreserve(who, amount);
+			Self::deposit_event(Event::DepositReleased { tx_id, who: who.clone(), amount });
+		}
+
+		/// removes a lock that is no longer pending from the amount locked of its asset
+		pub(crate) fn release_total_locked(lock_details: &LockDetailsOf<T>) {
+			<TotalLocked<T>>::mutate(lock_details.asset, |total| {
+				*total = total.saturating_sub(lock_details.amount)
+			});
+		}
+
+		/// refunds up to `limit` expired locks, oldest expiration first, and returns the weight
+		/// consumed. Expiration blocks without locks also count towards `limit`.
+		pub(crate) fn refund_expired(now: BlockNumberOf<T>, limit: u32) -> Weight {
+			let mut cursor = ExpiryCursor::<T>::get();
+			let mut refunded = 0u32;
+			let mut scanned = 0u32;
+			while cursor <= now && refunded < limit && scanned < limit {
+				scanned += 1;
+				let remaining = (limit - refunded) as usize;
+				let mut expiring: Vec<[u8; 32]> =
+					ExpiringLocks::<T>::iter_key_prefix(cursor).take(remaining + 1).collect();
+				let finished = expiring.len() <= remaining;
+				expiring.truncate(remaining);
+				for tx_id in expiring {
+					refunded += 1;
+					// a lock that can't be refunded leaves the index and waits for `cancel`.
+					<ExpiringLocks<T>>::remove(cursor, tx_id);
+					match with_storage_layer(|| Self::do_refund(tx_id)) {
+						Ok(lock_details) => Self::deposit_lock_event(
+							tx_id,
+							lock_details.hashlock,
+							Event::AutoRefunded {
+								tx_id,
+								sender: lock_details.sender,
+								recipient: lock_details.recipient,
+								asset: lock_details.asset,
+								asset_amount: lock_details.amount,
+							},
+						),
+						// time-based lock reached its estimated block early.
+						Err(e) if e == Error::<T>::TimeLockNotExpired.into() =>
+							Self::reschedule(tx_id, now),
+						Err(_) => {},
+					}
+				}
+				if !finished {
+					break
+				}
+				cursor.saturating_inc();
+			}
+			ExpiryCursor::<T>::put(cursor);
+
+			T::WeightInfo::refund_expired(refunded)
+				.saturating_add(T::DbWeight::get().reads_writes(scanned as u64 + 1, 1))
+		}
+
+		/// prunes up to `limit` locks settled at least `RetentionPeriod` blocks before `now`,
+		/// resuming from `PruneCursor`
+		pub(crate) fn prune_settled(now: BlockNumberOf<T>, limit: u32) -> Weight {
+			let retention = T::RetentionPeriod::get();
+			if now < retention {
+				return Weight::zero()
+			}
+			let last = now - retention;
+			let mut cursor = PruneCursor::<T>::get();
+			let mut pruned = 0u32;
+			let mut scanned = 0u32;
+			while cursor <= last && pruned < limit && scanned < limit {
+				scanned += 1;
+				let remaining = (limit - pruned) as usize;
+				let mut settled: Vec<[u8; 32]> =
+					SettledLocks::<T>::iter_key_prefix(cursor).take(remaining + 1).collect();
+				let finished = settled.len() <= remaining;
+				settled.truncate(remaining);
+				for tx_id in settled {
+					pruned += 1;
+					Self::prune(cursor, tx_id);
+				}
+				if !finished {
+					break
+				}
+				cursor.saturating_inc();
+			}
+			PruneCursor::<T>::put(cursor);
+
+			T::WeightInfo::prune_settled(pruned)
+				.saturating_add(T::DbWeight::get().reads_writes(scanned as u64 + 1, 1))
+		}
+
+		/// removes a lock settled at block `at` and its secret, keeping its id as used and
+		/// returning the rest of its deposit
+		fn prune(at: BlockNumberOf<T>, tx_id: [u8; 32]) {
+			<SettledLocks<T>>::remove(at, tx_id);
+			if let Some(lock_details) = <LockTransactions<T>>::take(tx_id) {
+				Self::release_deposit(tx_id, &lock_details.sender, lock_details.deposit);
+			}
+			<KnownSecrets<T>>::remove(tx_id);
+			<OversizedSecrets<T>>::remove(tx_id);
+			<PrunedTxIds<T>>::insert(tx_id, ());
+			Self::deposit_event(Event::Pruned { tx_id });
+		}
 	}
 	/// helpers functions to perform validations related to assets and perfom actions storage
 	/// related in relation to exchanges.
 	pub trait PalletHelpers: Config {
-		///	checks if a tx_id exists in the storage
+		///	checks if a tx_id exists in the storage or was used by a pruned lock
 		fn lock_details_exists(tx_id: [u8; 32]) -> bool;
+		/// ensures that the pallet is not paused or throws an Error
+		fn ensure_locks_allowed() -> Result<(), Error<Self>>;
+		/// ensures that the pallet is not paused with `PauseMode::All` or throws an Error
+		fn ensure_settlements_allowed() -> Result<(), Error<Self>>;
 		///	ensure that tx_id exists in the storage and who equals to recipient or throws error
 		fn ensure_lock_details_valid_to_unlock(
 			who: &AccountIdOf<Self>,
 			tx_id: [u8; 32],
 		) -> Result<(), Error<Self>>;
-		///	ensure that tx_id's hash and preimage's hash matches or throws error
+		///	ensure that tx_id exists in the storage and its recipient signed the claim for
+		/// `relayer_fee` or throws error
+		fn ensure_valid_claim_signature(
+			tx_id: [u8; 32],
+			relayer_fee: AssetBalanceOf<Self>,
+			signature: &Self::OffchainSignature,
+		) -> Result<(), Error<Self>>;
+		///	ensure that tx_id's hash and preimage's hash, computed with the lock's hash algorithm,
+		/// matches or throws error
 		fn ensure_hashlock_matches(tx_id: [u8; 32], preimage: Vec<u8>) -> Result<(), Error<Self>>;
+		///	ensure that the swap was neither claimed nor refunded or Error
+		fn ensure_pending(status: &SwapStatus<Self::BlockNumber>) -> Result<(), Error<Self>>;
 		///	ensure that tx_id's expiration block is in the past and it's refundable or Error
 		fn ensure_refundable(tx_id: [u8; 32]) -> Result<(), Error<Self>>;
-		///	ensure that tx_id's expiration block is valid and withdrawable or Error
+		///	ensure that tx_id is withdrawable, and not expired when `ClaimBeforeExpiry` is set, or
+		/// Error
 		fn ensure_withdrawable(tx_id: [u8; 32]) -> Result<(), Error<Self>>;
 		/// ensures that provided amount is above zero or throws an Error
 		fn ensure_is_not_zero(amount: AssetBalanceOf<Self>) -> Result<(), Error<Self>>;
-		/// checks if account has the amount expected to withdraw for the specific asset
+		/// checks if account has the amount expected to withdraw for the specific asset, and keeps
+		/// its minimum balance when `keep_alive` is set
 		fn ensure_has_balance(
 			who: &AccountIdOf<Self>,
-			asset_id: AssetIdOf<Self>,
+			asset: SwapAssetOf<Self>,
+			amount: AssetBalanceOf<Self>,
+			keep_alive: bool,
+		) -> Result<(), Error<Self>>;
+		/// checks that `who` can receive `amount` of the asset
+		fn ensure_can_receive(
+			who: &AccountIdOf<Self>,
+			asset: SwapAssetOf<Self>,
 			amount: AssetBalanceOf<Self>,
 		) -> Result<(), Error<Self>>;
 		/// asset exists or dispatchs an Error
-		fn ensure_asset_exists(asset_id: AssetIdOf<Self>) -> Result<(), Error<Self>>;
-		/// checks current block number with the deadline provided. Error if current block number is
-		/// above
-		fn ensure_valid_deadline(expiration_block: &Self::BlockNumber) -> Result<(), Error<Self>>;
-		/// checks current block number with the deadline provided. Error if current block number is
-		/// above
-		fn ensure_deadline(expiration_block: &Self::BlockNumber) -> Result<(), Error<Self>>;
-		/// checks current block number with the deadline provided. Error if block number has not
-		/// expired
-		fn ensure_expired(expiration_block: &Self::BlockNumber) -> Result<(), Error<Self>>;
+		fn ensure_asset_exists(asset: SwapAssetOf<Self>) -> Result<(), Error<Self>>;
+		/// checks a new lock of `amount` against the swap policy of the asset
+		fn ensure_policy_allows(
+			asset: SwapAssetOf<Self>,
+			amount: AssetBalanceOf<Self>,
+		) -> Result<(), Error<Self>>;
+		/// checks that `who` has not reached its open locks limit
+		fn ensure_can_open_lock(who: &AccountIdOf<Self>) -> Result<(), Error<Self>>;
+		/// checks the expiry provided is in the future and within `MinTimelock` and `MaxTimelock`
+		/// blocks from now, estimated for time-based expiries
+		fn ensure_valid_deadline(expiry: &ExpiryOf<Self>) -> Result<(), Error<Self>>;
+		/// checks that the deadline provided is at least `MinTimelockMargin` blocks before the
+		/// expiry of the pending locks with the same hashlock that `who` answers, sent by
+		/// `recipient` to `who`. Reads every pending lock of `recipient`
+		fn ensure_timelock_margin(
+			who: &AccountIdOf<Self>,
+			recipient: &AccountIdOf<Self>,
+			hashlock: [u8; 32],
+			expiration_block: &Self::BlockNumber,
+		) -> Result<(), Error<Self>>;
+		/// checks current block number or time with the expiry provided. Error if it has been
+		/// reached
+		fn ensure_deadline(expiry: &ExpiryOf<Self>) -> Result<(), Error<Self>>;
+		/// checks current block number or time with the expiry provided. Error if it has not been
+		/// reached
+		fn ensure_expired(expiry: &ExpiryOf<Self>) -> Result<(), Error<Self>>;
 	}
 
 	/// Helpers implementation
 	impl<T: Config> PalletHelpers for T {
 		fn lock_details_exists(tx_id: [u8; 32]) -> bool {
-			LockTransactions::<T>::contains_key(tx_id)
+			LockTransactions::<T>::contains_key(tx_id) || PrunedTxIds::<T>::contains_key(tx_id)
+		}
+		fn ensure_locks_allowed() -> Result<(), Error<Self>> {
+			ensure!(Paused::<T>::get().is_none(), Error::PalletPaused);
+			Ok(())
+		}
+		fn ensure_settlements_allowed() -> Result<(), Error<Self>> {
+			ensure!(Paused::<T>::get() != Some(PauseMode::All), Error::PalletPaused);
+			Ok(())
 		}
 		fn ensure_lock_details_valid_to_unlock(
 			who: &AccountIdOf<Self>,
@@ -214,28 +1626,51 @@ pub mod pallet {
 			ensure!(lock_details.recipient == who.clone(), Error::<T>::InvalidReceiver);
 			Ok(())
 		}
+		fn ensure_valid_claim_signature(
+			tx_id: [u8; 32],
+			relayer_fee: AssetBalanceOf<Self>,
+			signature: &Self::OffchainSignature,
+		) -> Result<(), Error<Self>> {
+			let lock_details =
+				LockTransactions::<T>::get(tx_id).ok_or(Error::TransactionNotExists)?;
+			let payload = Pallet::<T>::unlock_for_payload(tx_id, relayer_fee);
+			ensure!(
+				signature.verify(&payload[..], &lock_details.recipient),
+				Error::<T>::InvalidSignature
+			);
+			Ok(())
+		}
 		fn ensure_hashlock_matches(tx_id: [u8; 32], preimage: Vec<u8>) -> Result<(), Error<Self>> {
 			let lock_details =
 				LockTransactions::<T>::get(tx_id).ok_or(Error::TransactionNotExists)?;
-			let secret_hash: [u8; 32] = hashing::sha2_256(&preimage.as_slice().clone());
+			if T::StrictSecretLength::get() {
+				ensure!(preimage.len() == 32, Error::<T>::InvalidSecretLength);
+			}
+			let secret_hash = lock_details.hash_algorithm.hash(preimage.as_slice());
 			ensure!(lock_details.hashlock == secret_hash, Error::<T>::InvalidPreimage);
 			Ok(())
 		}
+		fn ensure_pending(status: &SwapStatus<Self::BlockNumber>) -> Result<(), Error<Self>> {
+			match status {
+				SwapStatus::Pending => Ok(()),
+				SwapStatus::Claimed { .. } => Err(Error::AlreadyWithdrawn),
+				SwapStatus::Refunded { .. } => Err(Error::AlreadyRefunded),
+			}
+		}
 		fn ensure_refundable(tx_id: [u8; 32]) -> Result<(), Error<Self>> {
 			let lock_details =
 				LockTransactions::<T>::get(tx_id).ok_or(Error::TransactionNotExists)?;
-			ensure!(lock_details.is_refunded == false, Error::<T>::AlreadyRefunded);
-			ensure!(lock_details.is_withdraw == false, Error::<T>::AlreadyWithdrawn);
-			Self::ensure_expired(&lock_details.expiration_block)?;
+			Self::ensure_pending(&lock_details.status)?;
+			Self::ensure_expired(&lock_details.expiry)?;
 			Ok(())
 		}
 		fn ensure_withdrawable(tx_id: [u8; 32]) -> Result<(), Error<Self>> {
 			let lock_details =
 				LockTransactions::<T>::get(tx_id).ok_or(Error::TransactionNotExists)?;
-			ensure!(lock_details.is_withdraw == false, Error::<T>::AlreadyWithdrawn);
-			ensure!(lock_details.is_refunded == false, Error::<T>::AlreadyRefunded);
-			// if we want to disallow claim to be made after the timeout, uncomment the following
-			// line Self::ensure_deadline(&lockDetails.expiration_block);
+			Self::ensure_pending(&lock_details.status)?;
+			if T::ClaimBeforeExpiry::get() {
+				Self::ensure_deadline(&lock_details.expiry)?;
+			}
 			Ok(())
 		}
 
@@ -245,153 +1680,456 @@ pub mod pallet {
 			Ok(())
 		}
 
-		/// checks if account has the amount expected to withdraw for the specific asset
+		/// checks if account has the amount expected to withdraw for the specific asset, and keeps
+		/// its minimum balance when `keep_alive` is set
 		fn ensure_has_balance(
 			who: &AccountIdOf<Self>,
-			asset_id: AssetIdOf<Self>,
+			asset: SwapAssetOf<Self>,
+			amount: AssetBalanceOf<Self>,
+			keep_alive: bool,
+		) -> Result<(), Error<Self>> {
+			match asset {
+				SwapAsset::Native => {
+					let new_balance = Self::Currency::free_balance(who)
+						.checked_sub(&amount)
+						.ok_or(Error::LowBalance)?;
+					Self::Currency::ensure_can_withdraw(
+						who,
+						amount,
+						WithdrawReasons::TRANSFER,
+						new_balance,
+					)
+					.map_err(|_| Error::BalanceFrozen)?;
+					let remaining = Self::Currency::total_balance(who).saturating_sub(amount);
+					ensure!(
+						!keep_alive || remaining >= Self::Currency::minimum_balance(),
+						Error::WouldReapSender
+					);
+					Ok(())
+				},
+				SwapAsset::Asset(asset_id) =>
+					match Self::Fungibles::can_withdraw(asset_id, who, amount) {
+						WithdrawConsequence::Success => Ok(()),
+						WithdrawConsequence::ReducedToZero(_) if !keep_alive => Ok(()),
+						WithdrawConsequence::ReducedToZero(_) | WithdrawConsequence::WouldDie =>
+							Err(Error::WouldReapSender),
+						WithdrawConsequence::NoFunds => Err(Error::LowBalance),
+						WithdrawConsequence::Frozen => Err(Error::BalanceFrozen),
+						WithdrawConsequence::UnknownAsset => Err(Error::TokenNotExists),
+						WithdrawConsequence::Underflow | WithdrawConsequence::Overflow =>
+							Err(Error::OverflowOrUnderflow),
+					},
+			}
+		}
+
+		/// checks that `who` can receive `amount` of the asset
+		fn ensure_can_receive(
+			who: &AccountIdOf<Self>,
+			asset: SwapAssetOf<Self>,
 			amount: AssetBalanceOf<Self>,
 		) -> Result<(), Error<Self>> {
-			match Self::Fungibles::can_withdraw(asset_id.clone(), who, amount) {
-				WithdrawConsequence::Success => Ok(()),
-				WithdrawConsequence::ReducedToZero(_) => Ok(()),
-				_ => Err(Error::LowBalance),
+			match asset {
+				SwapAsset::Native => {
+					let new_balance = Self::Currency::total_balance(who).saturating_add(amount);
+					ensure!(
+						new_balance >= Self::Currency::minimum_balance(),
+						Error::RecipientBelowMinimum
+					);
+					Ok(())
+				},
+				SwapAsset::Asset(asset_id) =>
+					match Self::Fungibles::can_deposit(asset_id, who, amount, false) {
+						DepositConsequence::Success => Ok(()),
+						DepositConsequence::BelowMinimum => Err(Error::RecipientBelowMinimum),
+						DepositConsequence::CannotCreate => Err(Error::RecipientCannotCreate),
+						DepositConsequence::UnknownAsset => Err(Error::TokenNotExists),
+						DepositConsequence::Overflow => Err(Error::OverflowOrUnderflow),
+					},
 			}
 		}
 
 		/// asset exists or dispatchs an Error
-		fn ensure_asset_exists(asset_id: AssetIdOf<Self>) -> Result<(), Error<Self>> {
-			ensure!(Self::Fungibles::asset_exists(asset_id), Error::TokenNotExists);
+		fn ensure_asset_exists(asset: SwapAssetOf<Self>) -> Result<(), Error<Self>> {
+			if let SwapAsset::Asset(asset_id) = asset {
+				ensure!(Self::Fungibles::asset_exists(asset_id), Error::TokenNotExists);
+			}
 			Ok(())
 		}
 
-		/// checks current block number with the deadline provided. Error if block number is above
-		fn ensure_valid_deadline(expiration_block: &Self::BlockNumber) -> Result<(), Error<Self>> {
-			ensure!(
-				expiration_block > &<frame_system::Pallet<Self>>::block_number(),
-				Error::InvalidTimelock
-			);
+		/// checks a new lock of `amount` against the swap policy of the asset
+		fn ensure_policy_allows(
+			asset: SwapAssetOf<Self>,
+			amount: AssetBalanceOf<Self>,
+		) -> Result<(), Error<Self>> {
+			let policy = match AssetPolicies::<T>::get(asset) {
+				Some(policy) => policy,
+				None => {
+					ensure!(!T::RequireAssetPolicy::get(), Error::AssetNotAllowed);
+					return Ok(())
+				},
+			};
+			ensure!(policy.enabled, Error::AssetDisabled);
+			ensure!(amount >= policy.min_amount, Error::AmountBelowMinimum);
+			if let Some(max_amount) = policy.max_amount {
+				ensure!(amount <= max_amount, Error::AmountAboveMaximum);
+			}
+			if let Some(tvl_cap) = policy.tvl_cap {
+				let total = TotalLocked::<T>::get(asset)
+					.checked_add(&amount)
+					.ok_or(Error::TvlCapExceeded)?;
+				ensure!(total <= tvl_cap, Error::TvlCapExceeded);
+			}
 			Ok(())
 		}
 
-		/// checks current block number with the deadline provided. Error if block number is above
-		fn ensure_deadline(expiration_block: &Self::BlockNumber) -> Result<(), Error<Self>> {
+		/// checks that `who` has not reached its open locks limit
+		fn ensure_can_open_lock(who: &AccountIdOf<Self>) -> Result<(), Error<Self>> {
 			ensure!(
-				expiration_block > &<frame_system::Pallet<Self>>::block_number(),
-				Error::Expired
+				OpenLockCount::<T>::get(who) < Pallet::<T>::open_lock_limit(who),
+				Error::TooManyOpenLocks
 			);
 			Ok(())
 		}
 
-		/// checks current block number with the deadline provided. Error if block number has not
-		/// expired
-		fn ensure_expired(expiration_block: &Self::BlockNumber) -> Result<(), Error<Self>> {
-			ensure!(
-				expiration_block <= &<frame_system::Pallet<Self>>::block_number(),
-				Error::TimeLockNotExpired
-			);
+		/// checks the expiry provided is in the future and within `MinTimelock` and `MaxTimelock`
+		/// blocks from now
+		fn ensure_valid_deadline(expiry: &ExpiryOf<Self>) -> Result<(), Error<Self>> {
+			Self::ensure_deadline(expiry).map_err(|_| Error::InvalidTimelock)?;
+			let now = <frame_system::Pallet<Self>>::block_number();
+			let timelock = Pallet::<T>::expected_block(expiry).saturating_sub(now);
+			ensure!(timelock >= T::MinTimelock::get(), Error::TimelockTooShort);
+			ensure!(timelock <= T::MaxTimelock::get(), Error::TimelockTooLong);
+			Ok(())
+		}
+
+		/// checks that the deadline provided is at least `MinTimelockMargin` blocks before the
+		/// expiry of the pending locks with the same hashlock sent by `recipient` to `who`
+		fn ensure_timelock_margin(
+			who: &AccountIdOf<Self>,
+			recipient: &AccountIdOf<Self>,
+			hashlock: [u8; 32],
+			expiration_block: &Self::BlockNumber,
+		) -> Result<(), Error<Self>> {
+			let margin = T::MinTimelockMargin::get();
+			if margin.is_zero() {
+				return Ok(())
+			}
+			let latest_allowed = expiration_block.saturating_add(margin);
+			// only `recipient` can add to its locks, unlike the locks of a public hashlock. All of
+			// them are read, `OpenLockCount` counts them for the weight of the call.
+			let answered = LocksBySender::<T>::iter_key_prefix(recipient)
+				.take(OpenLockCount::<T>::get(recipient) as usize)
+				.filter_map(LockTransactions::<T>::get)
+				.filter(|lock_details| {
+					lock_details.hashlock == hashlock && &lock_details.recipient == who
+				});
+			for lock_details in answered {
+				ensure!(
+					latest_allowed <= lock_details.expiration_block,
+					Error::TimelockMarginTooShort
+				);
+			}
+			Ok(())
+		}
+
+		/// checks current block number or time with the expiry provided. Error if it has been
+		/// reached
+		fn ensure_deadline(expiry: &ExpiryOf<Self>) -> Result<(), Error<Self>> {
+			ensure!(!Pallet::<T>::is_reached(expiry), Error::Expired);
+			Ok(())
+		}
+
+		/// checks current block number or time with the expiry provided. Error if it has not been
+		/// reached
+		fn ensure_expired(expiry: &ExpiryOf<Self>) -> Result<(), Error<Self>> {
+			ensure!(Pallet::<T>::is_reached(expiry), Error::TimeLockNotExpired);
 			Ok(())
 		}
 	}
 
+	/// Freezes the assets of pending `Custody::Hold` locks in the account of their sender.
+	impl<T: Config> pallet_assets::FrozenBalance<AssetIdOf<T>, AccountIdOf<T>, AssetBalanceOf<T>>
+		for Pallet<T>
+	{
+		fn frozen_balance(asset: AssetIdOf<T>, who: &AccountIdOf<T>) -> Option<AssetBalanceOf<T>> {
+			Some(HeldAssets::<T>::get(asset, who)).filter(|held| !held.is_zero())
+		}
+
+		fn died(asset: AssetIdOf<T>, who: &AccountIdOf<T>) {
+			HeldAssets::<T>::remove(asset, who);
+		}
+	}
+
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
 	// These functions materialize as "extrinsics", which are often compared to transactions.
 	// Dispatchable functions must be annotated with a weight and must return a DispatchResult.
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// Locks funds for a given time ( current block + timelock )
+		/// Locks funds for a given time ( current block + timelock ) or until a given timestamp.
+		/// The preimage revealed on unlock is checked against `hashlock` with `hash_algorithm`.
+		/// `tx_id` is empty when `DeriveTxIds` is set, the `Locked` event carries the derived id.
 		#[pallet::call_index(1)]
-		#[pallet::weight(10_000)]
+		#[pallet::weight(
+			T::WeightInfo::lock().saturating_add(Pallet::<T>::timelock_margin_weight(recipient))
+		)]
+		// the arguments are the call encoding, a struct would change it for every client
+		#[allow(clippy::too_many_arguments)]
 		pub fn lock(
 			origin: OriginFor<T>,
-			tx_id: [u8; 32],
+			tx_id: Option<[u8; 32]>,
 			recipient: AccountIdOf<T>,
 			hashlock: [u8; 32],
-			timelock: BlockNumberOf<T>,
+			hash_algorithm: HashAlgorithm,
+			timelock: TimelockOf<T>,
 			asset_id: AssetIdOf<T>,
 			asset_amount: AssetBalanceOf<T>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			ensure!(T::lock_details_exists(tx_id) == false, Error::<T>::TransactionIdExists);
-			let now = <frame_system::Pallet<T>>::block_number();
-			let expiration_block = now + timelock;
-			T::ensure_valid_deadline(&expiration_block)?;
-			T::ensure_asset_exists(asset_id)?;
-			T::ensure_has_balance(&who, asset_id, asset_amount)?;
-			// tokens transfered to pallet account.
-			T::Fungibles::transfer(asset_id, &who, &Self::account_id(), asset_amount, true)?;
-			<LockTransactions<T>>::insert(
-				tx_id,
-				LockDetails {
-					tx_id,
-					sender: who,
-					recipient: recipient.clone(),
-					asset_id,
-					amount: asset_amount,
-					hashlock,
-					expiration_block,
-					is_withdraw: false,
-					is_refunded: false,
-				},
-			);
-
-			Self::deposit_event(Event::Locked {
+			Self::do_lock(
+				who,
 				tx_id,
 				recipient,
 				hashlock,
-				expiration_block,
-				asset_id,
+				hash_algorithm,
+				timelock,
+				SwapAsset::Asset(asset_id),
 				asset_amount,
-			});
-
-			Ok(())
+			)
 		}
 
 		/// Unlocks funds if preimage is correct and timelock  has not expired
 		#[pallet::call_index(2)]
-		#[pallet::weight(10_000)]
+		#[pallet::weight(T::WeightInfo::unlock(preimage.len() as u32))]
 		pub fn unlock(origin: OriginFor<T>, tx_id: [u8; 32], preimage: Vec<u8>) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			T::ensure_settlements_allowed()?;
 			T::ensure_lock_details_valid_to_unlock(&who, tx_id)?;
-			T::ensure_hashlock_matches(tx_id, preimage.clone())?;
-			T::ensure_withdrawable(tx_id)?;
-			let mut lock_details =
-				LockTransactions::<T>::get(tx_id).ok_or(Error::<T>::TransactionNotExists)?;
-			T::ensure_has_balance(&Self::account_id(), lock_details.asset_id, lock_details.amount)?;
-			lock_details.is_withdraw = true;
-			T::Fungibles::transfer(
-				lock_details.asset_id,
-				&Self::account_id(),
-				&lock_details.recipient,
-				lock_details.amount,
-				true,
-			)?;
-			<LockTransactions<T>>::insert(tx_id, lock_details);
-			<KnownSecrets<T>>::insert(tx_id, preimage.clone());
-			Self::deposit_event(Event::Unlocked { tx_id });
-			Ok(())
+			Self::do_unlock(tx_id, preimage, None)
 		}
 
 		/// Called by the sender if there was no withdraw and the time lock has expired.
 		/// This will restore ownership of the tokens to the sender.
 		#[pallet::call_index(3)]
-		#[pallet::weight(10_000)]
+		#[pallet::weight(T::WeightInfo::cancel())]
 		pub fn cancel(origin: OriginFor<T>, tx_id: [u8; 32]) -> DispatchResult {
 			let _who = ensure_signed(origin)?;
+			T::ensure_settlements_allowed()?;
 			ensure!(T::lock_details_exists(tx_id) == true, Error::<T>::TransactionNotExists);
-			T::ensure_refundable(tx_id)?;
-			let mut lock_details =
+			let lock_details = Self::do_refund(tx_id)?;
+			Self::deposit_lock_event(
+				tx_id,
+				lock_details.hashlock,
+				Event::Canceled {
+					tx_id,
+					sender: lock_details.sender,
+					recipient: lock_details.recipient,
+					asset: lock_details.asset,
+					asset_amount: lock_details.amount,
+				},
+			);
+			Ok(())
+		}
+
+		/// Locks native currency for a given time ( current block + timelock ) or until a given
+		/// timestamp. Unlock and cancel work the same way as for asset locks.
+		#[pallet::call_index(4)]
+		#[pallet::weight(
+			T::WeightInfo::lock_native()
+				.saturating_add(Pallet::<T>::timelock_margin_weight(recipient))
+		)]
+		pub fn lock_native(
+			origin: OriginFor<T>,
+			tx_id: Option<[u8; 32]>,
+			recipient: AccountIdOf<T>,
+			hashlock: [u8; 32],
+			hash_algorithm: HashAlgorithm,
+			timelock: TimelockOf<T>,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_lock(
+				who,
+				tx_id,
+				recipient,
+				hashlock,
+				hash_algorithm,
+				timelock,
+				SwapAsset::Native,
+				amount,
+			)
+		}
+
+		/// Pauses the pallet. `PauseMode::LocksOnly` rejects new locks while in-flight swaps can
+		/// complete, `PauseMode::All` also freezes unlocks, cancels and automatic refunds.
+		#[pallet::call_index(5)]
+		#[pallet::weight(T::WeightInfo::pause())]
+		pub fn pause(origin: OriginFor<T>, mode: PauseMode) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			Paused::<T>::put(mode);
+			Self::deposit_event(Event::Paused { mode });
+			Ok(())
+		}
+
+		/// Lifts a pause set by `pause`.
+		#[pallet::call_index(6)]
+		#[pallet::weight(T::WeightInfo::unpause())]
+		pub fn unpause(origin: OriginFor<T>) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+			ensure!(Paused::<T>::take().is_some(), Error::<T>::NotPaused);
+			Self::deposit_event(Event::Unpaused);
+			Ok(())
+		}
+
+		/// Sets the limits applied to new locks of `asset`. Pending locks are not affected.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::WeightInfo::set_asset_policy())]
+		pub fn set_asset_policy(
+			origin: OriginFor<T>,
+			asset: SwapAssetOf<T>,
+			policy: AssetPolicyOf<T>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			if let Some(max_amount) = policy.max_amount {
+				ensure!(policy.min_amount <= max_amount, Error::<T>::InvalidAmount);
+			}
+			AssetPolicies::<T>::insert(asset, policy);
+			Self::deposit_event(Event::AssetPolicySet { asset, policy });
+			Ok(())
+		}
+
+		/// Removes the limits of `asset`. When `RequireAssetPolicy` is set the asset can't be
+		/// locked anymore.
+		#[pallet::call_index(8)]
+		#[pallet::weight(T::WeightInfo::remove_asset_policy())]
+		pub fn remove_asset_policy(origin: OriginFor<T>, asset: SwapAssetOf<T>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(AssetPolicies::<T>::take(asset).is_some(), Error::<T>::AssetPolicyNotFound);
+			Self::deposit_event(Event::AssetPolicyRemoved { asset });
+			Ok(())
+		}
+
+		/// Prunes a lock settled at least `RetentionPeriod` blocks ago and its secret. Anyone can
+		/// call it, and successful calls are free.
+		#[pallet::call_index(9)]
+		#[pallet::weight(T::WeightInfo::reap())]
+		pub fn reap(origin: OriginFor<T>, tx_id: [u8; 32]) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+			let lock_details =
 				LockTransactions::<T>::get(tx_id).ok_or(Error::<T>::TransactionNotExists)?;
-			lock_details.is_refunded = true;
-			T::Fungibles::transfer(
-				lock_details.asset_id,
-				&Self::account_id(),
-				&lock_details.sender,
-				lock_details.amount,
-				true,
-			)?;
-			<LockTransactions<T>>::insert(tx_id, lock_details.clone());
-			Self::deposit_event(Event::Canceled { tx_id });
+			let at = match lock_details.status {
+				SwapStatus::Pending => return Err(Error::<T>::SwapPending.into()),
+				SwapStatus::Claimed { at } | SwapStatus::Refunded { at } => at,
+			};
+			ensure!(
+				at.saturating_add(T::RetentionPeriod::get()) <=
+					<frame_system::Pallet<T>>::block_number(),
+				Error::<T>::RetentionNotElapsed
+			);
+			Self::prune(at, tx_id);
+			Ok(Pays::No.into())
+		}
+
+		/// Sets the open locks limit of `who`, for example to let a market maker keep more swaps
+		/// in flight. `None` resets it to `MaxOpenLocksPerAccount`. Pending locks are not
+		/// affected.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::WeightInfo::set_open_lock_limit())]
+		pub fn set_open_lock_limit(
+			origin: OriginFor<T>,
+			who: AccountIdOf<T>,
+			limit: Option<u32>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			OpenLockLimits::<T>::set(&who, limit);
+			Self::deposit_event(Event::OpenLockLimitSet { who, limit });
+			Ok(())
+		}
+
+		/// Sets the fee charged when new locks of `asset` are claimed, or removes it when `policy`
+		/// is `None`. Pending locks keep the fee they were created with.
+		#[pallet::call_index(11)]
+		#[pallet::weight(T::WeightInfo::set_fee_policy())]
+		pub fn set_fee_policy(
+			origin: OriginFor<T>,
+			asset: SwapAssetOf<T>,
+			policy: Option<FeePolicyOf<T>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			FeePolicies::<T>::set(asset, policy);
+			Self::deposit_event(Event::FeePolicySet { asset, policy });
 			Ok(())
 		}
+
+		/// Claims a lock for its recipient, who signed `Pallet::unlock_for_payload` off-chain
+		/// so any account can submit the claim and pay its transaction fees. The submitter is paid
+		/// `relayer_fee` out of the locked amount and the rest goes to the recipient.
+		#[pallet::call_index(12)]
+		#[pallet::weight(T::WeightInfo::unlock_for(preimage.len() as u32))]
+		pub fn unlock_for(
+			origin: OriginFor<T>,
+			tx_id: [u8; 32],
+			preimage: Vec<u8>,
+			recipient_signature: T::OffchainSignature,
+			relayer_fee: AssetBalanceOf<T>,
+		) -> DispatchResult {
+			let relayer = ensure_signed(origin)?;
+			T::ensure_settlements_allowed()?;
+			T::ensure_valid_claim_signature(tx_id, relayer_fee, &recipient_signature)?;
+			Self::do_unlock(tx_id, preimage, Some((relayer, relayer_fee)))
+		}
+
+		/// Unlocks funds for the recipient without a signed origin, so it can claim without
+		/// holding any balance. The preimage is the authorisation: the funds can only go to the
+		/// recipient, and the transaction pool only accepts valid preimages of pending locks.
+		#[pallet::call_index(13)]
+		#[pallet::weight(T::WeightInfo::unlock(preimage.len() as u32))]
+		pub fn unlock_unsigned(
+			origin: OriginFor<T>,
+			tx_id: [u8; 32],
+			preimage: Vec<u8>,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			T::ensure_settlements_allowed()?;
+			Self::do_unlock(tx_id, preimage, None)
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		/// Accepts `unlock_unsigned` calls revealing the secret of a pending lock. One claim per
+		/// lock is kept in the pool, with a priority growing and a longevity shrinking as the lock
+		/// gets closer to its expiry.
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			let (tx_id, preimage) = match call {
+				Call::unlock_unsigned { tx_id, preimage } => (*tx_id, preimage),
+				_ => return InvalidTransaction::Call.into(),
+			};
+			T::ensure_settlements_allowed().map_err(|_| InvalidTransaction::Call)?;
+			let lock_details =
+				LockTransactions::<T>::get(tx_id).ok_or(InvalidTransaction::Stale)?;
+			T::ensure_withdrawable(tx_id).map_err(|_| InvalidTransaction::Stale)?;
+			ensure!(
+				preimage.len() <= T::MaxPreimageLen::get() as usize,
+				InvalidTransaction::ExhaustsResources
+			);
+			T::ensure_hashlock_matches(tx_id, preimage.clone())
+				.map_err(|_| InvalidTransaction::BadProof)?;
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			let blocks_left =
+				lock_details.expiration_block.saturating_sub(now).saturated_into::<u64>();
+			let max_timelock = T::MaxTimelock::get().saturated_into::<u64>();
+			ValidTransaction::with_tag_prefix("AswapUnlock")
+				.priority(
+					T::UnsignedPriority::get()
+						.saturating_add(max_timelock.saturating_sub(blocks_left)),
+				)
+				.longevity(blocks_left.max(1))
+				.and_provides(tx_id)
+				.propagate(true)
+				.build()
+		}
 	}
 }
@@ -0,0 +1,951 @@
+//! Storage migrations for the aswap pallet.
+
+use super::*;
+use core::marker::PhantomData;
+use frame_support::{
+	inherent::Vec,
+	pallet_prelude::*,
+	sp_runtime::traits::{Saturating, Zero},
+	storage_alias,
+	traits::{GetStorageVersion, OnRuntimeUpgrade},
+};
+
+/// Migration to v1: `LockDetails.asset_id` becomes `LockDetails.asset` so locks can hold either
+/// the native currency or an asset. Every existing lock was created with `T::Fungibles`.
+pub mod v1 {
+	use super::*;
+
+	#[derive(Decode)]
+	struct OldLockDetails<AssetBalance, AssetId, AccountId, BlockNumber> {
+		tx_id: [u8; 32],
+		sender: AccountId,
+		recipient: AccountId,
+		asset_id: AssetId,
+		amount: AssetBalance,
+		hashlock: [u8; 32],
+		expiration_block: BlockNumber,
+		is_withdraw: bool,
+		is_refunded: bool,
+	}
+
+	type OldLockDetailsOf<T> =
+		OldLockDetails<AssetBalanceOf<T>, AssetIdOf<T>, AccountIdOf<T>, BlockNumberOf<T>>;
+
+	/// `LockDetails` as stored in v1.
+	#[derive(Encode, Decode)]
+	pub(super) struct LockDetailsV1<AssetBalance, AssetId, AccountId, BlockNumber> {
+		pub tx_id: [u8; 32],
+		pub sender: AccountId,
+		pub recipient: AccountId,
+		pub asset: SwapAsset<AssetId>,
+		pub amount: AssetBalance,
+		pub hashlock: [u8; 32],
+		pub expiration_block: BlockNumber,
+		pub is_withdraw: bool,
+		pub is_refunded: bool,
+	}
+
+	pub(super) type LockDetailsV1Of<T> =
+		LockDetailsV1<AssetBalanceOf<T>, AssetIdOf<T>, AccountIdOf<T>, BlockNumberOf<T>>;
+
+	#[storage_alias]
+	type LockTransactions<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, [u8; 32], LockDetailsV1Of<T>, OptionQuery>;
+
+	/// Wraps the asset id of every stored lock into `SwapAsset::Asset`.
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 0 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut translated = 0u64;
+			LockTransactions::<T>::translate::<OldLockDetailsOf<T>, _>(|_, old| {
+				translated += 1;
+				Some(LockDetailsV1 {
+					tx_id: old.tx_id,
+					sender: old.sender,
+					recipient: old.recipient,
+					asset: SwapAsset::Asset(old.asset_id),
+					amount: old.amount,
+					hashlock: old.hashlock,
+					expiration_block: old.expiration_block,
+					is_withdraw: old.is_withdraw,
+					is_refunded: old.is_refunded,
+				})
+			});
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 0, "aswap: expected storage v0");
+			let count = LockTransactions::<T>::iter_keys().count() as u32;
+			Ok(count.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let count: u32 = Decode::decode(&mut &state[..]).map_err(|_| "aswap: invalid state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 1, "aswap: expected storage v1");
+			ensure!(
+				LockTransactions::<T>::iter_values().count() as u32 == count,
+				"aswap: locks lost during migration"
+			);
+			Ok(())
+		}
+	}
+}
+
+/// Migration to v2: `LockDetails` records the hash algorithm of its hashlock. Every existing lock
+/// was created with SHA-256.
+pub mod v2 {
+	use super::*;
+	use v1::LockDetailsV1Of;
+
+	/// `LockDetails` as stored from v2 to v5.
+	#[derive(Encode, Decode)]
+	pub(crate) struct LockDetailsV2<AssetBalance, AssetId, AccountId, BlockNumber> {
+		pub tx_id: [u8; 32],
+		pub sender: AccountId,
+		pub recipient: AccountId,
+		pub asset: SwapAsset<AssetId>,
+		pub amount: AssetBalance,
+		pub hashlock: [u8; 32],
+		pub hash_algorithm: HashAlgorithm,
+		pub expiration_block: BlockNumber,
+		pub is_withdraw: bool,
+		pub is_refunded: bool,
+	}
+
+	pub(crate) type LockDetailsV2Of<T> =
+		LockDetailsV2<AssetBalanceOf<T>, AssetIdOf<T>, AccountIdOf<T>, BlockNumberOf<T>>;
+
+	#[storage_alias]
+	pub(crate) type LockTransactions<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, [u8; 32], LockDetailsV2Of<T>, OptionQuery>;
+
+	/// Sets `HashAlgorithm::Sha2_256` on every stored lock.
+	pub struct MigrateToV2<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 1 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut translated = 0u64;
+			LockTransactions::<T>::translate::<LockDetailsV1Of<T>, _>(|_, old| {
+				translated += 1;
+				Some(LockDetailsV2 {
+					tx_id: old.tx_id,
+					sender: old.sender,
+					recipient: old.recipient,
+					asset: old.asset,
+					amount: old.amount,
+					hashlock: old.hashlock,
+					hash_algorithm: HashAlgorithm::Sha2_256,
+					expiration_block: old.expiration_block,
+					is_withdraw: old.is_withdraw,
+					is_refunded: old.is_refunded,
+				})
+			});
+			StorageVersion::new(2).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 1, "aswap: expected storage v1");
+			let count = LockTransactions::<T>::iter_keys().count() as u32;
+			Ok(count.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let count: u32 = Decode::decode(&mut &state[..]).map_err(|_| "aswap: invalid state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 2, "aswap: expected storage v2");
+			ensure!(
+				LockTransactions::<T>::iter_values().count() as u32 == count,
+				"aswap: locks lost during migration"
+			);
+			Ok(())
+		}
+	}
+}
+
+/// Migration to v3: pending locks are indexed by expiration block so they are refunded
+/// automatically once expired.
+pub mod v3 {
+	use super::*;
+
+	/// Fills `ExpiringLocks` with every pending lock and starts the refund cursor at the oldest
+	/// expiration block.
+	pub struct MigrateToV3<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 2 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut reads = 1u64;
+			let mut writes = 1u64;
+			let mut cursor = frame_system::Pallet::<T>::block_number();
+			for (tx_id, lock_details) in v2::LockTransactions::<T>::iter() {
+				reads += 1;
+				if lock_details.is_withdraw || lock_details.is_refunded {
+					continue
+				}
+				writes += 1;
+				cursor = cursor.min(lock_details.expiration_block);
+				ExpiringLocks::<T>::insert(lock_details.expiration_block, tx_id, ());
+			}
+			ExpiryCursor::<T>::put(cursor);
+			StorageVersion::new(3).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(reads, writes + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 2, "aswap: expected storage v2");
+			let pending = v2::LockTransactions::<T>::iter_values()
+				.filter(|lock_details| !lock_details.is_withdraw && !lock_details.is_refunded)
+				.count() as u32;
+			Ok(pending.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let pending: u32 =
+				Decode::decode(&mut &state[..]).map_err(|_| "aswap: invalid state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 3, "aswap: expected storage v3");
+			ensure!(
+				ExpiringLocks::<T>::iter_keys().count() as u32 == pending,
+				"aswap: pending locks missing from the expiry index"
+			);
+			Ok(())
+		}
+	}
+}
+
+/// Migration to v4: pending locks are indexed by sender, recipient and hashlock.
+pub mod v4 {
+	use super::*;
+
+	/// Fills `LocksBySender`, `LocksByRecipient` and `LocksByHashlock` with every pending lock.
+	pub struct MigrateToV4<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV4<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 3 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut reads = 1u64;
+			let mut writes = 1u64;
+			for (tx_id, lock_details) in v2::LockTransactions::<T>::iter() {
+				reads += 1;
+				if lock_details.is_withdraw || lock_details.is_refunded {
+					continue
+				}
+				writes += 3;
+				LocksBySender::<T>::insert(&lock_details.sender, tx_id, ());
+				LocksByRecipient::<T>::insert(&lock_details.recipient, tx_id, ());
+				LocksByHashlock::<T>::insert(lock_details.hashlock, tx_id, ());
+			}
+			StorageVersion::new(4).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(reads, writes)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 3, "aswap: expected storage v3");
+			let pending = v2::LockTransactions::<T>::iter_values()
+				.filter(|lock_details| !lock_details.is_withdraw && !lock_details.is_refunded)
+				.count() as u32;
+			Ok(pending.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let pending: u32 =
+				Decode::decode(&mut &state[..]).map_err(|_| "aswap: invalid state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 4, "aswap: expected storage v4");
+			ensure!(
+				LocksBySender::<T>::iter_keys().count() as u32 == pending &&
+					LocksByRecipient::<T>::iter_keys().count() as u32 == pending &&
+					LocksByHashlock::<T>::iter_keys().count() as u32 == pending,
+				"aswap: pending locks missing from the indexes"
+			);
+			Ok(())
+		}
+	}
+}
+
+/// Migration to v5: `KnownSecrets` holds bounded preimages. Secrets longer than
+/// `Config::MaxPreimageLen` are moved to `OversizedSecrets`, where counterparties can still read
+/// them to claim the other leg of their swap. Secrets longer than `Config::MaxLegacyPreimageLen`
+/// can't be kept: `pre_upgrade` fails on them so the bound can be raised first.
+pub mod v5 {
+	use super::*;
+
+	#[cfg(any(feature = "try-runtime", test))]
+	pub(crate) mod old {
+		use super::*;
+
+		#[storage_alias]
+		pub type KnownSecrets<T: Config> =
+			StorageMap<Pallet<T>, Blake2_128Concat, [u8; 32], Vec<u8>, OptionQuery>;
+	}
+
+	/// Bounds every stored secret to `Config::MaxPreimageLen`, moving the longer ones to
+	/// `OversizedSecrets` and dropping those over `Config::MaxLegacyPreimageLen`.
+	pub struct MigrateToV5<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV5<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 4 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let max_len = T::MaxPreimageLen::get() as usize;
+			let mut translated = 0u64;
+			let mut oversized = 0u64;
+			KnownSecrets::<T>::translate::<Vec<u8>, _>(|tx_id, secret| {
+				translated += 1;
+				if secret.len() > max_len {
+					if let Ok(secret) = LegacyPreimageOf::<T>::try_from(secret) {
+						oversized += 1;
+						OversizedSecrets::<T>::insert(tx_id, secret);
+					}
+					return None
+				}
+				PreimageOf::<T>::try_from(secret).ok()
+			});
+			StorageVersion::new(5).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(translated + 1, translated + oversized + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 4, "aswap: expected storage v4");
+			let max_len = T::MaxPreimageLen::get() as usize;
+			let max_legacy_len = T::MaxLegacyPreimageLen::get() as usize;
+			let (mut bounded, mut oversized) = (0u32, 0u32);
+			for secret in old::KnownSecrets::<T>::iter_values() {
+				ensure!(
+					secret.len() <= max_legacy_len,
+					"aswap: secret longer than MaxLegacyPreimageLen would be lost"
+				);
+				if secret.len() > max_len {
+					oversized += 1;
+				} else {
+					bounded += 1;
+				}
+			}
+			Ok((bounded, oversized).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let (bounded, oversized): (u32, u32) =
+				Decode::decode(&mut &state[..]).map_err(|_| "aswap: invalid state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 5, "aswap: expected storage v5");
+			ensure!(
+				KnownSecrets::<T>::iter_values().count() as u32 == bounded,
+				"aswap: bounded secrets lost during migration"
+			);
+			ensure!(
+				OversizedSecrets::<T>::iter_values().count() as u32 == oversized,
+				"aswap: oversized secrets lost during migration"
+			);
+			Ok(())
+		}
+	}
+}
+
+/// Migration to v6: `LockDetails.is_withdraw` and `LockDetails.is_refunded` are replaced by a
+/// `SwapStatus`. The block where a swap was settled was never stored, so settled swaps record the
+/// block of the upgrade.
+pub mod v6 {
+	use super::*;
+	use v2::LockDetailsV2Of;
+
+	/// `LockDetails` as stored from v6 to v7.
+	#[derive(Encode, Decode)]
+	pub(crate) struct LockDetailsV6<AssetBalance, AssetId, AccountId, BlockNumber> {
+		pub tx_id: [u8; 32],
+		pub sender: AccountId,
+		pub recipient: AccountId,
+		pub asset: SwapAsset<AssetId>,
+		pub amount: AssetBalance,
+		pub hashlock: [u8; 32],
+		pub hash_algorithm: HashAlgorithm,
+		pub expiration_block: BlockNumber,
+		pub status: SwapStatus<BlockNumber>,
+	}
+
+	pub(crate) type LockDetailsV6Of<T> =
+		LockDetailsV6<AssetBalanceOf<T>, AssetIdOf<T>, AccountIdOf<T>, BlockNumberOf<T>>;
+
+	#[storage_alias]
+	pub(crate) type LockTransactions<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, [u8; 32], LockDetailsV6Of<T>, OptionQuery>;
+
+	/// Turns the flags of every stored lock into a `SwapStatus`.
+	pub struct MigrateToV6<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV6<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 5 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let at = frame_system::Pallet::<T>::block_number();
+			let mut translated = 0u64;
+			LockTransactions::<T>::translate::<LockDetailsV2Of<T>, _>(|_, old| {
+				translated += 1;
+				let status = match (old.is_withdraw, old.is_refunded) {
+					(false, false) => SwapStatus::Pending,
+					(true, _) => SwapStatus::Claimed { at },
+					(false, true) => SwapStatus::Refunded { at },
+				};
+				Some(LockDetailsV6 {
+					tx_id: old.tx_id,
+					sender: old.sender,
+					recipient: old.recipient,
+					asset: old.asset,
+					amount: old.amount,
+					hashlock: old.hashlock,
+					hash_algorithm: old.hash_algorithm,
+					expiration_block: old.expiration_block,
+					status,
+				})
+			});
+			StorageVersion::new(6).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(translated + 2, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 5, "aswap: expected storage v5");
+			let mut counts = (0u32, 0u32, 0u32);
+			for lock_details in v2::LockTransactions::<T>::iter_values() {
+				match (lock_details.is_withdraw, lock_details.is_refunded) {
+					(false, false) => counts.0 += 1,
+					(true, _) => counts.1 += 1,
+					(false, true) => counts.2 += 1,
+				}
+			}
+			Ok(counts.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let counts: (u32, u32, u32) =
+				Decode::decode(&mut &state[..]).map_err(|_| "aswap: invalid state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 6, "aswap: expected storage v6");
+			let mut migrated = (0u32, 0u32, 0u32);
+			for lock_details in LockTransactions::<T>::iter_values() {
+				match lock_details.status {
+					SwapStatus::Pending => migrated.0 += 1,
+					SwapStatus::Claimed { .. } => migrated.1 += 1,
+					SwapStatus::Refunded { .. } => migrated.2 += 1,
+				}
+			}
+			ensure!(migrated == counts, "aswap: lock statuses changed during migration");
+			Ok(())
+		}
+	}
+}
+
+/// Migration to v7: the amount held by pending locks is tracked per asset to enforce the total
+/// value locked cap of its `AssetPolicy`.
+pub mod v7 {
+	use super::*;
+
+	/// Fills `TotalLocked` from every pending lock.
+	pub struct MigrateToV7<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV7<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 6 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut reads = 1u64;
+			let mut writes = 1u64;
+			for lock_details in v6::LockTransactions::<T>::iter_values() {
+				reads += 1;
+				if !lock_details.status.is_pending() {
+					continue
+				}
+				writes += 1;
+				TotalLocked::<T>::mutate(lock_details.asset, |total| {
+					*total = total.saturating_add(lock_details.amount)
+				});
+			}
+			StorageVersion::new(7).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(reads, writes)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 6, "aswap: expected storage v6");
+			ensure!(TotalLocked::<T>::iter_keys().next().is_none(), "aswap: TotalLocked not empty");
+			Ok(Vec::new())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_: Vec<u8>) -> Result<(), &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 7, "aswap: expected storage v7");
+			for (asset, total) in TotalLocked::<T>::iter() {
+				let pending = v6::LockTransactions::<T>::iter_values()
+					.filter(|lock_details| {
+						lock_details.status.is_pending() && lock_details.asset == asset
+					})
+					.fold(AssetBalanceOf::<T>::default(), |total, lock_details| {
+						total.saturating_add(lock_details.amount)
+					});
+				ensure!(total == pending, "aswap: TotalLocked doesn't match pending locks");
+			}
+			Ok(())
+		}
+	}
+}
+
+/// Migration to v8: locks can expire at a timestamp. Every existing lock expires at its block.
+pub mod v8 {
+	use super::*;
+
+	/// `LockDetails` as stored from v8 to v9.
+	#[derive(Encode, Decode)]
+	pub(crate) struct LockDetailsV8<AssetBalance, AssetId, AccountId, BlockNumber, Moment> {
+		pub tx_id: [u8; 32],
+		pub sender: AccountId,
+		pub recipient: AccountId,
+		pub asset: SwapAsset<AssetId>,
+		pub amount: AssetBalance,
+		pub hashlock: [u8; 32],
+		pub hash_algorithm: HashAlgorithm,
+		pub expiry: Expiry<BlockNumber, Moment>,
+		pub expiration_block: BlockNumber,
+		pub status: SwapStatus<BlockNumber>,
+	}
+
+	pub(crate) type LockDetailsV8Of<T> = LockDetailsV8<
+		AssetBalanceOf<T>,
+		AssetIdOf<T>,
+		AccountIdOf<T>,
+		BlockNumberOf<T>,
+		MomentOf<T>,
+	>;
+
+	#[storage_alias]
+	pub(crate) type LockTransactions<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, [u8; 32], LockDetailsV8Of<T>, OptionQuery>;
+
+	/// Sets `Expiry::AtBlock` on every stored lock.
+	pub struct MigrateToV8<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV8<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 7 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut translated = 0u64;
+			LockTransactions::<T>::translate::<v6::LockDetailsV6Of<T>, _>(|_, old| {
+				translated += 1;
+				Some(LockDetailsV8 {
+					tx_id: old.tx_id,
+					sender: old.sender,
+					recipient: old.recipient,
+					asset: old.asset,
+					amount: old.amount,
+					hashlock: old.hashlock,
+					hash_algorithm: old.hash_algorithm,
+					expiry: Expiry::AtBlock(old.expiration_block),
+					expiration_block: old.expiration_block,
+					status: old.status,
+				})
+			});
+			StorageVersion::new(8).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 7, "aswap: expected storage v7");
+			Ok((v6::LockTransactions::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let count: u32 = Decode::decode(&mut &state[..]).map_err(|_| "aswap: invalid state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 8, "aswap: expected storage v8");
+			let mut migrated = 0u32;
+			for lock_details in LockTransactions::<T>::iter_values() {
+				ensure!(
+					lock_details.expiry == Expiry::AtBlock(lock_details.expiration_block),
+					"aswap: lock expiry doesn't match its block"
+				);
+				migrated += 1;
+			}
+			ensure!(migrated == count, "aswap: locks lost during migration");
+			Ok(())
+		}
+	}
+}
+
+/// Migration to v9: settled locks are pruned once `RetentionPeriod` has elapsed. Locks settled
+/// before the upgrade are indexed by the block where they were settled.
+pub mod v9 {
+	use super::*;
+
+	/// Fills `SettledLocks` from every claimed or refunded lock and starts the pruning cursor at
+	/// the oldest settlement block.
+	pub struct MigrateToV9<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV9<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 8 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut reads = 1u64;
+			let mut writes = 1u64;
+			let mut cursor = frame_system::Pallet::<T>::block_number();
+			for (tx_id, lock_details) in v8::LockTransactions::<T>::iter() {
+				reads += 1;
+				match lock_details.status {
+					SwapStatus::Pending => continue,
+					SwapStatus::Claimed { at } | SwapStatus::Refunded { at } => {
+						writes += 1;
+						cursor = cursor.min(at);
+						SettledLocks::<T>::insert(at, tx_id, ());
+					},
+				}
+			}
+			PruneCursor::<T>::put(cursor);
+			StorageVersion::new(9).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(reads + 1, writes + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 8, "aswap: expected storage v8");
+			let settled = v8::LockTransactions::<T>::iter_values()
+				.filter(|lock_details| !lock_details.status.is_pending())
+				.count() as u32;
+			Ok(settled.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let settled: u32 =
+				Decode::decode(&mut &state[..]).map_err(|_| "aswap: invalid state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 9, "aswap: expected storage v9");
+			ensure!(
+				SettledLocks::<T>::iter_keys().count() as u32 == settled,
+				"aswap: SettledLocks doesn't match settled locks"
+			);
+			ensure!(
+				SettledLocks::<T>::iter_keys().all(|(at, _)| PruneCursor::<T>::get() <= at),
+				"aswap: settled locks behind the prune cursor"
+			);
+			Ok(())
+		}
+	}
+}
+
+/// Migration to v10: the sender of a lock reserves a deposit for its storage. Locks created
+/// before the upgrade hold no deposit.
+pub mod v10 {
+	use super::*;
+
+	/// `LockDetails` as stored from v10 to v11.
+	#[derive(Encode, Decode)]
+	pub(super) struct LockDetailsV10<AssetBalance, AssetId, AccountId, BlockNumber, Moment> {
+		pub tx_id: [u8; 32],
+		pub sender: AccountId,
+		pub recipient: AccountId,
+		pub asset: SwapAsset<AssetId>,
+		pub amount: AssetBalance,
+		pub hashlock: [u8; 32],
+		pub hash_algorithm: HashAlgorithm,
+		pub expiry: Expiry<BlockNumber, Moment>,
+		pub expiration_block: BlockNumber,
+		pub status: SwapStatus<BlockNumber>,
+		pub deposit: AssetBalance,
+	}
+
+	pub(super) type LockDetailsV10Of<T> = LockDetailsV10<
+		AssetBalanceOf<T>,
+		AssetIdOf<T>,
+		AccountIdOf<T>,
+		BlockNumberOf<T>,
+		MomentOf<T>,
+	>;
+
+	#[storage_alias]
+	pub(super) type LockTransactions<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, [u8; 32], LockDetailsV10Of<T>, OptionQuery>;
+
+	/// Sets a zero deposit on every stored lock.
+	pub struct MigrateToV10<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV10<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 9 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut translated = 0u64;
+			LockTransactions::<T>::translate::<v8::LockDetailsV8Of<T>, _>(|_, old| {
+				translated += 1;
+				Some(LockDetailsV10 {
+					tx_id: old.tx_id,
+					sender: old.sender,
+					recipient: old.recipient,
+					asset: old.asset,
+					amount: old.amount,
+					hashlock: old.hashlock,
+					hash_algorithm: old.hash_algorithm,
+					expiry: old.expiry,
+					expiration_block: old.expiration_block,
+					status: old.status,
+					deposit: Default::default(),
+				})
+			});
+			StorageVersion::new(10).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 9, "aswap: expected storage v9");
+			Ok((v8::LockTransactions::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let count: u32 = Decode::decode(&mut &state[..]).map_err(|_| "aswap: invalid state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 10, "aswap: expected storage v10");
+			ensure!(
+				LockTransactions::<T>::iter_values().count() as u32 == count,
+				"aswap: locks lost during migration"
+			);
+			Ok(())
+		}
+	}
+}
+
+/// Migration to v11: the pending locks of each sender are counted in `OpenLockCount` so they can
+/// be limited by `MaxOpenLocksPerAccount`.
+pub mod v11 {
+	use super::*;
+
+	/// Counts the pending locks of every sender from `LocksBySender`.
+	pub struct MigrateToV11<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV11<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 10 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut reads = 1u64;
+			let mut writes = 1u64;
+			for (sender, _) in LocksBySender::<T>::iter_keys() {
+				reads += 1;
+				writes += 1;
+				OpenLockCount::<T>::mutate(&sender, |count| *count = count.saturating_add(1));
+			}
+			StorageVersion::new(11).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(reads, writes)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 10, "aswap: expected storage v10");
+			Ok((LocksBySender::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let pending: u32 =
+				Decode::decode(&mut &state[..]).map_err(|_| "aswap: invalid state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 11, "aswap: expected storage v11");
+			ensure!(
+				OpenLockCount::<T>::iter_values().sum::<u32>() == pending,
+				"aswap: OpenLockCount doesn't match pending locks"
+			);
+			Ok(())
+		}
+	}
+}
+
+/// Migration to v12: the funds of a lock can be held in the sender's account. Every existing lock
+/// keeps its funds in the pallet account.
+pub mod v12 {
+	use super::*;
+
+	/// `LockDetails` as stored in v12.
+	#[derive(Encode, Decode)]
+	pub(super) struct LockDetailsV12<AssetBalance, AssetId, AccountId, BlockNumber, Moment> {
+		pub tx_id: [u8; 32],
+		pub sender: AccountId,
+		pub recipient: AccountId,
+		pub asset: SwapAsset<AssetId>,
+		pub amount: AssetBalance,
+		pub hashlock: [u8; 32],
+		pub hash_algorithm: HashAlgorithm,
+		pub expiry: Expiry<BlockNumber, Moment>,
+		pub expiration_block: BlockNumber,
+		pub status: SwapStatus<BlockNumber>,
+		pub deposit: AssetBalance,
+		pub custody: Custody,
+	}
+
+	pub(super) type LockDetailsV12Of<T> = LockDetailsV12<
+		AssetBalanceOf<T>,
+		AssetIdOf<T>,
+		AccountIdOf<T>,
+		BlockNumberOf<T>,
+		MomentOf<T>,
+	>;
+
+	#[storage_alias]
+	pub(super) type LockTransactions<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, [u8; 32], LockDetailsV12Of<T>, OptionQuery>;
+
+	/// Sets `Custody::PalletAccount` on every stored lock.
+	pub struct MigrateToV12<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV12<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 11 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut translated = 0u64;
+			LockTransactions::<T>::translate::<v10::LockDetailsV10Of<T>, _>(|_, old| {
+				translated += 1;
+				Some(LockDetailsV12 {
+					tx_id: old.tx_id,
+					sender: old.sender,
+					recipient: old.recipient,
+					asset: old.asset,
+					amount: old.amount,
+					hashlock: old.hashlock,
+					hash_algorithm: old.hash_algorithm,
+					expiry: old.expiry,
+					expiration_block: old.expiration_block,
+					status: old.status,
+					deposit: old.deposit,
+					custody: Custody::PalletAccount,
+				})
+			});
+			StorageVersion::new(12).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 11, "aswap: expected storage v11");
+			Ok((v10::LockTransactions::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let count: u32 = Decode::decode(&mut &state[..]).map_err(|_| "aswap: invalid state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 12, "aswap: expected storage v12");
+			let mut migrated = 0u32;
+			for lock_details in LockTransactions::<T>::iter_values() {
+				ensure!(
+					lock_details.custody == Custody::PalletAccount,
+					"aswap: lock custody isn't the pallet account"
+				);
+				migrated += 1;
+			}
+			ensure!(migrated == count, "aswap: locks lost during migration");
+			Ok(())
+		}
+	}
+}
+
+/// Migration to v13: a lock stores the fee charged when it is claimed. Existing locks were created
+/// without a fee.
+pub mod v13 {
+	use super::*;
+
+	/// Sets a zero fee on every stored lock.
+	pub struct MigrateToV13<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV13<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 12 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut translated = 0u64;
+			LockTransactions::<T>::translate::<v12::LockDetailsV12Of<T>, _>(|_, old| {
+				translated += 1;
+				Some(LockDetails {
+					tx_id: old.tx_id,
+					sender: old.sender,
+					recipient: old.recipient,
+					asset: old.asset,
+					amount: old.amount,
+					hashlock: old.hashlock,
+					hash_algorithm: old.hash_algorithm,
+					expiry: old.expiry,
+					expiration_block: old.expiration_block,
+					status: old.status,
+					deposit: old.deposit,
+					custody: old.custody,
+					fee: Default::default(),
+				})
+			});
+			StorageVersion::new(13).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 12, "aswap: expected storage v12");
+			Ok((v12::LockTransactions::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let count: u32 = Decode::decode(&mut &state[..]).map_err(|_| "aswap: invalid state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 13, "aswap: expected storage v13");
+			let mut migrated = 0u32;
+			for lock_details in LockTransactions::<T>::iter_values() {
+				ensure!(lock_details.fee.is_zero(), "aswap: lock fee isn't zero");
+				migrated += 1;
+			}
+			ensure!(migrated == count, "aswap: locks lost during migration");
+			Ok(())
+		}
+	}
+}
@@ -1,17 +1,21 @@
 use crate as pallet_aswap;
 use crate::mock_data::*;
+use codec::{Decode, Encode};
 use frame_support::{
 	parameter_types,
 	traits::{
 		fungibles, AsEnsureOriginWithArg, ConstU128, ConstU16, ConstU32, ConstU64, GenesisBuild,
+		Hooks,
 	},
 	PalletId,
 };
 use frame_system::{EnsureRoot, EnsureSigned};
+use scale_info::TypeInfo;
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header,
-	traits::{BlakeTwo256, IdentityLookup},
+	traits::{BlakeTwo256, IdentifyAccount, IdentityLookup, Lazy, Verify},
+	RuntimeDebug,
 };
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -19,6 +23,31 @@ type Block = frame_system::mocking::MockBlock<Test>;
 
 type Balance = u128;
 
+/// Accounts are 16 bytes long, so the escrow sub-accounts of different swaps are told apart.
+pub type AccountId = u128;
+
+/// Public key of `MockSignature`, the account itself.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct MockSigner(pub AccountId);
+
+impl IdentifyAccount for MockSigner {
+	type AccountId = AccountId;
+	fn into_account(self) -> AccountId {
+		self.0
+	}
+}
+
+/// Signature of the mock, valid for the account and message it holds.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct MockSignature(pub AccountId, pub Vec<u8>);
+
+impl Verify for MockSignature {
+	type Signer = MockSigner;
+	fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &AccountId) -> bool {
+		signer == &self.0 && msg.get() == &self.1[..]
+	}
+}
+
 // Configure a mock runtime to test the pallet.
 frame_support::construct_runtime!(
 	pub enum Test where
@@ -29,6 +58,7 @@ frame_support::construct_runtime!(
 		System: frame_system,
 		Balances: pallet_balances,
 		Assets: pallet_assets,
+		Timestamp: pallet_timestamp,
 		Aswap: pallet_aswap,
 	}
 );
@@ -44,7 +74,7 @@ impl frame_system::Config for Test {
 	type BlockNumber = u64;
 	type Hash = H256;
 	type Hashing = BlakeTwo256;
-	type AccountId = u64;
+	type AccountId = AccountId;
 	type Lookup = IdentityLookup<Self::AccountId>;
 	type Header = Header;
 	type RuntimeEvent = RuntimeEvent;
@@ -72,7 +102,7 @@ impl pallet_balances::Config for Test {
 	type AccountStore = System;
 	type WeightInfo = ();
 	type MaxLocks = MaxLocks;
-	type MaxReserves = ();
+	type MaxReserves = ConstU32<2>;
 	type ReserveIdentifier = [u8; 8];
 }
 
@@ -98,14 +128,40 @@ impl pallet_assets::Config for Test {
 	type MetadataDepositPerByte = MetadataDepositPerByte;
 	type ApprovalDeposit = ApprovalDeposit;
 	type StringLimit = StringLimit;
-	type Freezer = ();
+	type Freezer = Aswap;
 	type Extra = ();
 	type WeightInfo = ();
 	type RemoveItemsLimit = ConstU32<1000>;
 }
 
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<1>;
+	type WeightInfo = ();
+}
+
 parameter_types! {
 	pub const AswapPalletId: PalletId = PalletId(*b"aswapjur");
+	pub static DepositBase: Balance = 0;
+	pub static DepositPerByte: Balance = 0;
+	pub static StrictSecretLength: bool = false;
+	pub static MaxPreimageLen: u32 = 256;
+	pub static MaxLegacyPreimageLen: u32 = 1024;
+	pub static ClaimBeforeExpiry: bool = false;
+	pub static RequireAssetPolicy: bool = false;
+	pub static MinTimelock: u64 = 1;
+	pub static MaxTimelock: u64 = 1_000;
+	pub static MinTimelockMargin: u64 = 0;
+	pub static ExpectedBlockTime: u64 = 6_000;
+	pub static DeriveTxIds: bool = false;
+	pub static MaxAutoRefundsPerBlock: u32 = 10;
+	pub static RetentionPeriod: u64 = 100;
+	pub static MaxOpenLocksPerAccount: u32 = 50;
+	pub static LockCustody: pallet_aswap::Custody = pallet_aswap::Custody::PalletAccount;
+	pub static KeepSenderAlive: bool = true;
+	pub const FeeDestination: AccountId = ACCOUNT_FEES;
+	pub const UnsignedPriority: u64 = 1_000_000;
 }
 
 impl pallet_aswap::Config for Test {
@@ -113,6 +169,46 @@ impl pallet_aswap::Config for Test {
 	type Currency = Balances;
 	type Fungibles = Assets;
 	type PalletId = AswapPalletId;
+	type DepositBase = DepositBase;
+	type DepositPerByte = DepositPerByte;
+	type StrictSecretLength = StrictSecretLength;
+	type MaxPreimageLen = MaxPreimageLen;
+	type MaxLegacyPreimageLen = MaxLegacyPreimageLen;
+	type MinTimelock = MinTimelock;
+	type MaxTimelock = MaxTimelock;
+	type MinTimelockMargin = MinTimelockMargin;
+	type DeriveTxIds = DeriveTxIds;
+	type Time = Timestamp;
+	type ExpectedBlockTime = ExpectedBlockTime;
+	type ClaimBeforeExpiry = ClaimBeforeExpiry;
+	type PauseOrigin = EnsureRoot<Self::AccountId>;
+	type AdminOrigin = EnsureRoot<Self::AccountId>;
+	type RequireAssetPolicy = RequireAssetPolicy;
+	type MaxAutoRefundsPerBlock = MaxAutoRefundsPerBlock;
+	type RetentionPeriod = RetentionPeriod;
+	type MaxOpenLocksPerAccount = MaxOpenLocksPerAccount;
+	type KeepSenderAlive = KeepSenderAlive;
+	type LockCustody = LockCustody;
+	type FeeDestination = FeeDestination;
+	type UnsignedPriority = UnsignedPriority;
+	type OffchainSignature = MockSignature;
+	type OffchainPublic = MockSigner;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = AswapBenchmarkHelper;
+	type WeightInfo = ();
+}
+
+#[cfg(feature = "runtime-benchmarks")]
+pub struct AswapBenchmarkHelper;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_aswap::BenchmarkHelper<MockSigner, MockSignature> for AswapBenchmarkHelper {
+	fn signer() -> MockSigner {
+		MockSigner(ACCOUNT_E_NO_NATIVE)
+	}
+	fn sign(signer: &MockSigner, message: &[u8]) -> MockSignature {
+		MockSignature(signer.0, message.to_vec())
+	}
 }
 
 pub fn get_pallet_balance(asset_id: u32) -> Balance {
@@ -123,10 +219,26 @@ pub fn get_pallet_balance(asset_id: u32) -> Balance {
 	)
 }
 
-pub fn get_account_balance(account_id: u64, asset_id: u32) -> Balance {
+pub fn get_account_balance(account_id: AccountId, asset_id: u32) -> Balance {
 	<<Test as crate::Config>::Fungibles as fungibles::Inspect<_>>::balance(asset_id, &account_id)
 }
 
+pub fn get_native_balance(account_id: AccountId) -> Balance {
+	Balances::free_balance(account_id)
+}
+
+/// moves the chain forward running the pallet hooks on every block and checking the state of
+/// the pallet after each one. Every block is produced `ExpectedBlockTime` after the previous one.
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		let block = System::block_number() + 1;
+		System::set_block_number(block);
+		Timestamp::set_timestamp(block * ExpectedBlockTime::get());
+		Aswap::on_initialize(block);
+		Aswap::do_try_state().expect("pallet state is consistent");
+	}
+}
+
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
 	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
@@ -171,6 +283,9 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 	.unwrap();
 
 	let mut test_ext: sp_io::TestExternalities = storage.into();
-	test_ext.execute_with(|| System::set_block_number(1));
+	test_ext.execute_with(|| {
+		System::set_block_number(1);
+		Timestamp::set_timestamp(ExpectedBlockTime::get());
+	});
 	test_ext
 }
@@ -1,7 +1,9 @@
-pub(crate) const ACCOUNT_A: u64 = 0;
-pub(crate) const ACCOUNT_B: u64 = 1;
-pub(crate) const ACCOUNT_C: u64 = 2;
-pub(crate) const ACCOUNT_D_LOW_BALANCES: u64 = 3;
+pub(crate) const ACCOUNT_A: u128 = 0;
+pub(crate) const ACCOUNT_B: u128 = 1;
+pub(crate) const ACCOUNT_C: u128 = 2;
+pub(crate) const ACCOUNT_D_LOW_BALANCES: u128 = 3;
+pub(crate) const ACCOUNT_FEES: u128 = 4;
+pub(crate) const ACCOUNT_E_NO_NATIVE: u128 = 5;
 pub(crate) const ACCOUNTS_START_BALANCE: u128 = 1_000_000_000;
 pub(crate) const ACCOUNTS_START_LOW_BALANCE: u128 = 2;
 pub(crate) const PALLET_START_BALANCE: u128 = 1;
@@ -1,6 +1,22 @@
-use crate::{mock::*, mock_data::*, Error};
-use codec::Encode;
-use frame_support::{assert_noop, assert_ok, sp_io::hashing};
+use crate::{
+	migrations, mock::*, mock_data::*, AssetPolicy, Custody, Error, ExpiringLocks, Expiry,
+	FeePolicy, HashAlgorithm, HeldAssets, LockDetailsOf, LockStatus, LockTransactions,
+	LocksByHashlock, LocksByRecipient, LocksBySender, OversizedSecrets, PauseMode, PreimageOf,
+	PruneCursor, SettledLocks, SwapAsset, SwapStatus, Timelock, TotalLocked,
+};
+use codec::{Encode, MaxEncodedLen};
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::Pays,
+	sp_io::hashing,
+	traits::{Hooks, NamedReservableCurrency, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+use sp_runtime::{
+	traits::ValidateUnsigned,
+	transaction_validity::{InvalidTransaction, TransactionSource, TransactionValidity},
+	Permill,
+};
 
 /// Account A wants to swap 1_000 units of A per 1_000 units of B with Account B
 #[test]
@@ -17,10 +33,11 @@ fn lock_ok() {
 		assert_eq!(get_pallet_balance(ASSET_A), PALLET_START_BALANCE);
 		assert_ok!(Aswap::lock(
 			RuntimeOrigin::signed(ACCOUNT_A),
-			tx_id,
+			Some(tx_id),
 			ACCOUNT_B,
 			hash,
-			timelock,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
 			ASSET_A,
 			asset_amount
 		));
@@ -30,11 +47,10 @@ fn lock_ok() {
 		assert_eq!(lock_details.amount, asset_amount);
 		assert_eq!(lock_details.sender, ACCOUNT_A);
 		assert_eq!(lock_details.recipient, ACCOUNT_B);
-		assert_eq!(lock_details.asset_id, ASSET_A);
+		assert_eq!(lock_details.asset, SwapAsset::Asset(ASSET_A));
 		assert_eq!(lock_details.hashlock, hash);
 		assert_eq!(lock_details.expiration_block, timelock + 1);
-		assert_eq!(lock_details.is_withdraw, false);
-		assert_eq!(lock_details.is_refunded, false);
+		assert_eq!(lock_details.status, SwapStatus::Pending);
 	});
 }
 
@@ -52,10 +68,11 @@ fn lock_validations_ok() {
 		assert_eq!(get_pallet_balance(ASSET_A), PALLET_START_BALANCE);
 		assert_ok!(Aswap::lock(
 			RuntimeOrigin::signed(ACCOUNT_A),
-			tx_id,
+			Some(tx_id),
 			ACCOUNT_B,
 			hash,
-			timelock,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
 			ASSET_A,
 			asset_amount
 		));
@@ -65,20 +82,20 @@ fn lock_validations_ok() {
 		assert_eq!(lock_details.amount, asset_amount);
 		assert_eq!(lock_details.sender, ACCOUNT_A);
 		assert_eq!(lock_details.recipient, ACCOUNT_B);
-		assert_eq!(lock_details.asset_id, ASSET_A);
+		assert_eq!(lock_details.asset, SwapAsset::Asset(ASSET_A));
 		assert_eq!(lock_details.hashlock, hash);
 		assert_eq!(lock_details.expiration_block, timelock + 1);
-		assert_eq!(lock_details.is_withdraw, false);
-		assert_eq!(lock_details.is_refunded, false);
+		assert_eq!(lock_details.status, SwapStatus::Pending);
 
 		//trying to add unsigned
 		assert_noop!(
 			Aswap::lock(
 				RuntimeOrigin::none(),
-				tx_id,
+				Some(tx_id),
 				ACCOUNT_B,
 				hash,
-				timelock,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
 				ASSET_A,
 				asset_amount
 			),
@@ -89,10 +106,11 @@ fn lock_validations_ok() {
 		assert_noop!(
 			Aswap::lock(
 				RuntimeOrigin::signed(ACCOUNT_B),
-				tx_id,
+				Some(tx_id),
 				ACCOUNT_C,
 				hash,
-				timelock,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
 				ASSET_A,
 				asset_amount
 			),
@@ -107,10 +125,11 @@ fn lock_validations_ok() {
 		assert_noop!(
 			Aswap::lock(
 				RuntimeOrigin::signed(ACCOUNT_B),
-				new_tx_id,
+				Some(new_tx_id),
 				ACCOUNT_C,
 				hash,
-				0,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(0),
 				ASSET_A,
 				asset_amount
 			),
@@ -121,10 +140,11 @@ fn lock_validations_ok() {
 		assert_noop!(
 			Aswap::lock(
 				RuntimeOrigin::signed(ACCOUNT_B),
-				new_tx_id,
+				Some(new_tx_id),
 				ACCOUNT_C,
 				hash,
-				timelock,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
 				ASSET_NOT_EXIST,
 				asset_amount
 			),
@@ -135,10 +155,11 @@ fn lock_validations_ok() {
 		assert_noop!(
 			Aswap::lock(
 				RuntimeOrigin::signed(ACCOUNT_D_LOW_BALANCES),
-				new_tx_id,
+				Some(new_tx_id),
 				ACCOUNT_C,
 				hash,
-				timelock,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
 				ASSET_A,
 				asset_amount
 			),
@@ -161,10 +182,11 @@ fn unlock_ok() {
 		assert_eq!(get_pallet_balance(ASSET_A), PALLET_START_BALANCE);
 		assert_ok!(Aswap::lock(
 			RuntimeOrigin::signed(ACCOUNT_A),
-			tx_id,
+			Some(tx_id),
 			ACCOUNT_B,
 			hash,
-			timelock,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
 			ASSET_A,
 			asset_amount
 		));
@@ -174,17 +196,17 @@ fn unlock_ok() {
 		assert_eq!(lock_details.amount, asset_amount);
 		assert_eq!(lock_details.sender, ACCOUNT_A);
 		assert_eq!(lock_details.recipient, ACCOUNT_B);
-		assert_eq!(lock_details.asset_id, ASSET_A);
+		assert_eq!(lock_details.asset, SwapAsset::Asset(ASSET_A));
 		assert_eq!(lock_details.hashlock, hash);
 		assert_eq!(lock_details.expiration_block, timelock + 1);
-		assert_eq!(lock_details.is_withdraw, false);
-		assert_eq!(lock_details.is_refunded, false);
+		assert_eq!(lock_details.status, SwapStatus::Pending);
 
 		//Account b unlocking
 		assert_eq!(get_account_balance(ACCOUNT_B, ASSET_A), ACCOUNTS_START_BALANCE);
 		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()));
 		assert_eq!(get_account_balance(ACCOUNT_B, ASSET_A), ACCOUNTS_START_BALANCE + asset_amount);
 		assert_eq!(get_pallet_balance(ASSET_A), PALLET_START_BALANCE);
+		assert_eq!(Aswap::lock_transactions(tx_id).unwrap().status, SwapStatus::Claimed { at: 1 });
 
 		//reveled secret
 		let known_secret = Aswap::known_secrets(tx_id).unwrap();
@@ -206,10 +228,11 @@ fn cancel_ok() {
 		assert_eq!(get_pallet_balance(ASSET_A), PALLET_START_BALANCE);
 		assert_ok!(Aswap::lock(
 			RuntimeOrigin::signed(ACCOUNT_A),
-			tx_id,
+			Some(tx_id),
 			ACCOUNT_B,
 			hash,
-			timelock,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
 			ASSET_A,
 			asset_amount
 		));
@@ -219,11 +242,10 @@ fn cancel_ok() {
 		assert_eq!(lock_details.amount, asset_amount);
 		assert_eq!(lock_details.sender, ACCOUNT_A);
 		assert_eq!(lock_details.recipient, ACCOUNT_B);
-		assert_eq!(lock_details.asset_id, ASSET_A);
+		assert_eq!(lock_details.asset, SwapAsset::Asset(ASSET_A));
 		assert_eq!(lock_details.hashlock, hash);
 		assert_eq!(lock_details.expiration_block, timelock + 1);
-		assert_eq!(lock_details.is_withdraw, false);
-		assert_eq!(lock_details.is_refunded, false);
+		assert_eq!(lock_details.status, SwapStatus::Pending);
 
 		//Account A cancelling
 		System::set_block_number(10);
@@ -231,5 +253,1823 @@ fn cancel_ok() {
 		assert_ok!(Aswap::cancel(RuntimeOrigin::signed(ACCOUNT_A), tx_id));
 		assert_eq!(get_account_balance(ACCOUNT_A, ASSET_A), ACCOUNTS_START_BALANCE);
 		assert_eq!(get_pallet_balance(ASSET_A), PALLET_START_BALANCE);
+		assert_eq!(
+			Aswap::lock_transactions(tx_id).unwrap().status,
+			SwapStatus::Refunded { at: 10 }
+		);
+	});
+}
+
+#[test]
+fn lock_native_unlock_ok() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let tx_id_elements = (ACCOUNT_A, ACCOUNT_B, hash, timelock, amount).encode();
+		let tx_id = hashing::sha2_256(&tx_id_elements.as_slice());
+
+		let pallet_start_balance = get_native_balance(Aswap::account_id());
+		assert_ok!(Aswap::lock_native(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(tx_id),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			amount
+		));
+		assert_eq!(get_native_balance(Aswap::account_id()), pallet_start_balance + amount);
+		assert_eq!(get_native_balance(ACCOUNT_A), ACCOUNTS_START_BALANCE - amount);
+
+		let lock_details = Aswap::lock_transactions(tx_id).unwrap();
+		assert_eq!(lock_details.asset, SwapAsset::Native);
+		assert_eq!(lock_details.amount, amount);
+
+		//Account b unlocking
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()));
+		assert_eq!(get_native_balance(ACCOUNT_B), ACCOUNTS_START_BALANCE + amount);
+		assert_eq!(get_native_balance(Aswap::account_id()), pallet_start_balance);
+	});
+}
+
+#[test]
+fn lock_native_cancel_ok() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let tx_id_elements = (ACCOUNT_A, ACCOUNT_B, hash, timelock, amount).encode();
+		let tx_id = hashing::sha2_256(&tx_id_elements.as_slice());
+
+		//trying with origin with low balance
+		assert_noop!(
+			Aswap::lock_native(
+				RuntimeOrigin::signed(ACCOUNT_D_LOW_BALANCES),
+				Some(tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				amount
+			),
+			Error::<Test>::LowBalance
+		);
+
+		assert_ok!(Aswap::lock_native(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(tx_id),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			amount
+		));
+
+		//Account A cancelling
+		System::set_block_number(10);
+		assert_ok!(Aswap::cancel(RuntimeOrigin::signed(ACCOUNT_A), tx_id));
+		assert_eq!(get_native_balance(ACCOUNT_A), ACCOUNTS_START_BALANCE);
+	});
+}
+
+#[test]
+fn unlock_with_hash_algorithms_ok() {
+	new_test_ext().execute_with(|| {
+		let secret = [7u8; 32];
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let algorithms = [
+			HashAlgorithm::Sha2_256,
+			HashAlgorithm::Keccak256,
+			HashAlgorithm::Blake2_256,
+			HashAlgorithm::Hash160,
+		];
+
+		for (index, algorithm) in algorithms.into_iter().enumerate() {
+			let hash = algorithm.hash(&secret);
+			let tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash, index as u32).encode());
+			assert_ok!(Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(tx_id),
+				ACCOUNT_B,
+				hash,
+				algorithm,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				asset_amount
+			));
+			assert_eq!(Aswap::lock_transactions(tx_id).unwrap().hash_algorithm, algorithm);
+
+			//a sha256 preimage check must not pass for other algorithms
+			if algorithm != HashAlgorithm::Sha2_256 {
+				assert_noop!(
+					Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, b"wrong".to_vec()),
+					Error::<Test>::InvalidPreimage
+				);
+			}
+			assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()));
+		}
+		assert_eq!(
+			get_account_balance(ACCOUNT_B, ASSET_A),
+			ACCOUNTS_START_BALANCE + 4 * asset_amount
+		);
+
+		//hash160 keeps the 20-byte digest and pads the rest with zeros
+		let hash160 = HashAlgorithm::Hash160.hash(&secret);
+		assert_eq!(hash160[20..], [0u8; 12]);
+	});
+}
+
+#[test]
+fn unlock_strict_secret_length() {
+	new_test_ext().execute_with(|| {
+		StrictSecretLength::set(&true);
+		let short_secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(short_secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash).encode());
+
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(tx_id),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			ASSET_A,
+			asset_amount
+		));
+		assert_noop!(
+			Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, short_secret.to_vec()),
+			Error::<Test>::InvalidSecretLength
+		);
+	});
+}
+
+#[test]
+fn unlock_after_expiry() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash).encode());
+
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(tx_id),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			ASSET_A,
+			asset_amount
+		));
+		//no automatic refund has run yet
+		System::set_block_number(timelock + 1);
+		ClaimBeforeExpiry::set(&true);
+		assert_noop!(
+			Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()),
+			Error::<Test>::Expired
+		);
+
+		//late unlocks are allowed when the deadline is not enforced
+		ClaimBeforeExpiry::set(&false);
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()));
+	});
+}
+
+#[test]
+fn unlock_preimage_too_long() {
+	new_test_ext().execute_with(|| {
+		MaxPreimageLen::set(&16);
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash).encode());
+
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(tx_id),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			ASSET_A,
+			asset_amount
+		));
+		assert_noop!(
+			Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()),
+			Error::<Test>::PreimageTooLong
+		);
+
+		MaxPreimageLen::set(&32);
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()));
+		assert_eq!(Aswap::known_secrets(tx_id).unwrap().into_inner(), secret.to_vec());
+	});
+}
+
+#[test]
+fn expired_locks_are_refunded_automatically() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		MaxAutoRefundsPerBlock::set(&2);
+
+		let tx_ids: Vec<[u8; 32]> = (0..3u32)
+			.map(|index| hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash, index).encode()))
+			.collect();
+		for tx_id in tx_ids.iter() {
+			assert_ok!(Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(*tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				asset_amount
+			));
+		}
+		//the first lock is claimed and must not be refunded
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_ids[0], secret.to_vec()));
+		assert_eq!(get_account_balance(ACCOUNT_A, ASSET_A), ACCOUNTS_START_BALANCE - 3_000);
+
+		//nothing happens before the expiration block
+		run_to_block(timelock);
+		assert_eq!(get_account_balance(ACCOUNT_A, ASSET_A), ACCOUNTS_START_BALANCE - 3_000);
+
+		run_to_block(timelock + 1);
+		assert_eq!(get_account_balance(ACCOUNT_A, ASSET_A), ACCOUNTS_START_BALANCE - 1_000);
+		for tx_id in &tx_ids[1..] {
+			assert_eq!(
+				Aswap::lock_transactions(tx_id).unwrap().status,
+				SwapStatus::Refunded { at: timelock + 1 }
+			);
+		}
+		assert_eq!(
+			Aswap::lock_transactions(tx_ids[0]).unwrap().status,
+			SwapStatus::Claimed { at: 1 }
+		);
+		System::assert_has_event(
+			crate::Event::AutoRefunded {
+				tx_id: tx_ids[1],
+				sender: ACCOUNT_A,
+				recipient: ACCOUNT_B,
+				asset: SwapAsset::Asset(ASSET_A),
+				asset_amount,
+			}
+			.into(),
+		);
+		assert_eq!(get_pallet_balance(ASSET_A), PALLET_START_BALANCE);
+
+		//refunded locks can't be canceled again
+		assert_noop!(
+			Aswap::cancel(RuntimeOrigin::signed(ACCOUNT_A), tx_ids[1]),
+			Error::<Test>::AlreadyRefunded
+		);
+	});
+}
+
+#[test]
+fn auto_refunds_are_bounded_per_block() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		MaxAutoRefundsPerBlock::set(&2);
+
+		let tx_ids: Vec<[u8; 32]> = (0..3u32)
+			.map(|index| hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash, index).encode()))
+			.collect();
+		for tx_id in tx_ids.iter() {
+			assert_ok!(Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(*tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				asset_amount
+			));
+		}
+
+		//only two locks fit in the expiration block
+		run_to_block(timelock + 1);
+		assert_eq!(get_account_balance(ACCOUNT_A, ASSET_A), ACCOUNTS_START_BALANCE - 1_000);
+
+		//the remaining one is refunded in the next block
+		run_to_block(timelock + 2);
+		assert_eq!(get_account_balance(ACCOUNT_A, ASSET_A), ACCOUNTS_START_BALANCE);
+		assert_eq!(get_pallet_balance(ASSET_A), PALLET_START_BALANCE);
+	});
+}
+
+#[test]
+fn indexes_follow_pending_locks() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash, 0u32).encode());
+		let other_tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_C, hash, 1u32).encode());
+
+		for (id, recipient) in [(tx_id, ACCOUNT_B), (other_tx_id, ACCOUNT_C)] {
+			assert_ok!(Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(id),
+				recipient,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				asset_amount
+			));
+		}
+		assert!(LocksBySender::<Test>::contains_key(ACCOUNT_A, tx_id));
+		assert!(LocksByRecipient::<Test>::contains_key(ACCOUNT_B, tx_id));
+		assert_eq!(LocksBySender::<Test>::iter_key_prefix(ACCOUNT_A).count(), 2);
+		assert_eq!(LocksByHashlock::<Test>::iter_key_prefix(hash).count(), 2);
+
+		//claimed locks leave the indexes
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()));
+		assert!(!LocksBySender::<Test>::contains_key(ACCOUNT_A, tx_id));
+		assert!(!LocksByRecipient::<Test>::contains_key(ACCOUNT_B, tx_id));
+		assert!(!LocksByHashlock::<Test>::contains_key(hash, tx_id));
+		assert!(!ExpiringLocks::<Test>::contains_key(timelock + 1, tx_id));
+
+		//refunded locks leave the indexes
+		System::set_block_number(10);
+		assert_ok!(Aswap::cancel(RuntimeOrigin::signed(ACCOUNT_A), other_tx_id));
+		assert_eq!(LocksBySender::<Test>::iter_key_prefix(ACCOUNT_A).count(), 0);
+		assert_eq!(LocksByRecipient::<Test>::iter_key_prefix(ACCOUNT_C).count(), 0);
+		assert_eq!(LocksByHashlock::<Test>::iter_key_prefix(hash).count(), 0);
+	});
+}
+
+#[test]
+fn query_helpers_report_locks() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let tx_ids: Vec<[u8; 32]> = (0u32..3)
+			.map(|index| hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash, index).encode()))
+			.collect();
+
+		for tx_id in &tx_ids {
+			assert_ok!(Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(*tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				asset_amount
+			));
+		}
+		assert_eq!(Aswap::lock_status([0u8; 32]), None);
+		assert_eq!(Aswap::lock_status(tx_ids[0]), Some(LockStatus::Pending));
+
+		//pages are ordered by tx_id and resume after the cursor
+		let mut sorted = tx_ids.clone();
+		sorted.sort();
+		let page = Aswap::locks_for_account(&ACCOUNT_B, None, 2);
+		assert_eq!(page.iter().map(|lock| lock.tx_id).collect::<Vec<_>>(), sorted[..2].to_vec());
+		let page = Aswap::locks_for_account(&ACCOUNT_A, Some(sorted[1]), 2);
+		assert_eq!(page.iter().map(|lock| lock.tx_id).collect::<Vec<_>>(), sorted[2..].to_vec());
+		assert!(Aswap::locks_for_account(&ACCOUNT_C, None, 2).is_empty());
+
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_ids[0], secret.to_vec()));
+		assert_eq!(Aswap::lock_status(tx_ids[0]), Some(LockStatus::Claimed));
+		assert_eq!(Aswap::locks_for_account(&ACCOUNT_B, None, 10).len(), 2);
+
+		System::set_block_number(10);
+		assert_eq!(Aswap::lock_status(tx_ids[1]), Some(LockStatus::Expired));
+		assert_ok!(Aswap::cancel(RuntimeOrigin::signed(ACCOUNT_A), tx_ids[1]));
+		assert_eq!(Aswap::lock_status(tx_ids[1]), Some(LockStatus::Refunded));
+	});
+}
+
+#[test]
+fn pause_blocks_locks() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash, 0u32).encode());
+		let other_tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash, 1u32).encode());
+
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(tx_id),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			ASSET_A,
+			asset_amount
+		));
+		assert_noop!(
+			Aswap::pause(RuntimeOrigin::signed(ACCOUNT_A), PauseMode::LocksOnly),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Aswap::pause(RuntimeOrigin::root(), PauseMode::LocksOnly));
+		System::assert_last_event(crate::Event::Paused { mode: PauseMode::LocksOnly }.into());
+
+		assert_noop!(
+			Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(other_tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				asset_amount
+			),
+			Error::<Test>::PalletPaused
+		);
+		assert_noop!(
+			Aswap::lock_native(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(other_tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				asset_amount
+			),
+			Error::<Test>::PalletPaused
+		);
+		//in-flight swaps can still complete
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()));
+
+		assert_ok!(Aswap::unpause(RuntimeOrigin::root()));
+		System::assert_last_event(crate::Event::Unpaused.into());
+		assert_noop!(Aswap::unpause(RuntimeOrigin::root()), Error::<Test>::NotPaused);
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(other_tx_id),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			ASSET_A,
+			asset_amount
+		));
+	});
+}
+
+#[test]
+fn pause_all_blocks_settlements() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash, 0u32).encode());
+		let other_tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash, 1u32).encode());
+
+		for id in [tx_id, other_tx_id] {
+			assert_ok!(Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				asset_amount
+			));
+		}
+		assert_ok!(Aswap::pause(RuntimeOrigin::root(), PauseMode::All));
+		assert_noop!(
+			Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()),
+			Error::<Test>::PalletPaused
+		);
+
+		//expired locks are neither canceled nor refunded automatically
+		run_to_block(timelock + 1);
+		assert_noop!(
+			Aswap::cancel(RuntimeOrigin::signed(ACCOUNT_A), tx_id),
+			Error::<Test>::PalletPaused
+		);
+		assert_eq!(Aswap::lock_status(other_tx_id), Some(LockStatus::Expired));
+
+		//refunds resume once unpaused
+		assert_ok!(Aswap::unpause(RuntimeOrigin::root()));
+		run_to_block(timelock + 2);
+		assert_eq!(Aswap::lock_status(tx_id), Some(LockStatus::Refunded));
+		assert_eq!(Aswap::lock_status(other_tx_id), Some(LockStatus::Refunded));
+	});
+}
+
+#[test]
+fn lock_zero_amount_fails() {
+	new_test_ext().execute_with(|| {
+		let hash = hashing::sha2_256(b"Something between us 2023");
+		let tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash).encode());
+		assert_noop!(
+			Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(5),
+				ASSET_A,
+				0
+			),
+			Error::<Test>::InvalidAmount
+		);
+	});
+}
+
+#[test]
+fn asset_policy_limits_locks() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		//5 blocks
+		let timelock = 5;
+		let tx_ids: Vec<[u8; 32]> = (0u32..4)
+			.map(|index| hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash, index).encode()))
+			.collect();
+		let lock = |tx_id: [u8; 32], asset_id: u32, amount: u128| {
+			Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				asset_id,
+				amount,
+			)
+		};
+		let policy = AssetPolicy {
+			enabled: true,
+			min_amount: 100,
+			max_amount: Some(1_000),
+			tvl_cap: Some(1_500),
+		};
+
+		assert_noop!(
+			Aswap::set_asset_policy(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				SwapAsset::Asset(ASSET_A),
+				policy
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Aswap::set_asset_policy(
+			RuntimeOrigin::root(),
+			SwapAsset::Asset(ASSET_A),
+			policy
+		));
+		System::assert_last_event(
+			crate::Event::AssetPolicySet { asset: SwapAsset::Asset(ASSET_A), policy }.into(),
+		);
+
+		assert_noop!(lock(tx_ids[0], ASSET_A, 99), Error::<Test>::AmountBelowMinimum);
+		assert_noop!(lock(tx_ids[0], ASSET_A, 1_001), Error::<Test>::AmountAboveMaximum);
+		assert_ok!(lock(tx_ids[0], ASSET_A, 1_000));
+		assert_eq!(Aswap::total_locked(SwapAsset::Asset(ASSET_A)), 1_000);
+		assert_noop!(lock(tx_ids[1], ASSET_A, 501), Error::<Test>::TvlCapExceeded);
+		assert_ok!(lock(tx_ids[1], ASSET_A, 500));
+
+		//settled locks free room under the cap
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_ids[0], secret.to_vec()));
+		assert_eq!(Aswap::total_locked(SwapAsset::Asset(ASSET_A)), 500);
+		assert_ok!(lock(tx_ids[2], ASSET_A, 1_000));
+
+		assert_ok!(Aswap::set_asset_policy(
+			RuntimeOrigin::root(),
+			SwapAsset::Asset(ASSET_A),
+			AssetPolicy { enabled: false, ..policy }
+		));
+		assert_noop!(lock(tx_ids[3], ASSET_A, 100), Error::<Test>::AssetDisabled);
+
+		//assets without policy are rejected when a policy is required
+		RequireAssetPolicy::set(&true);
+		assert_noop!(lock(tx_ids[3], ASSET_B, 100), Error::<Test>::AssetNotAllowed);
+		assert_ok!(Aswap::remove_asset_policy(RuntimeOrigin::root(), SwapAsset::Asset(ASSET_A)));
+		System::assert_last_event(
+			crate::Event::AssetPolicyRemoved { asset: SwapAsset::Asset(ASSET_A) }.into(),
+		);
+		assert_noop!(
+			Aswap::remove_asset_policy(RuntimeOrigin::root(), SwapAsset::Asset(ASSET_A)),
+			Error::<Test>::AssetPolicyNotFound
+		);
+		assert_noop!(lock(tx_ids[3], ASSET_A, 100), Error::<Test>::AssetNotAllowed);
+	});
+}
+
+#[test]
+fn timelock_bounds() {
+	new_test_ext().execute_with(|| {
+		MinTimelock::set(&5);
+		MaxTimelock::set(&10);
+		let hash = hashing::sha2_256(b"Something between us 2023");
+		let tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash).encode());
+		let lock = |timelock: u64| {
+			Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				1_000,
+			)
+		};
+
+		assert_noop!(lock(4), Error::<Test>::TimelockTooShort);
+		assert_noop!(lock(11), Error::<Test>::TimelockTooLong);
+		assert_ok!(lock(10));
+	});
+}
+
+#[test]
+fn timelock_margin_for_reused_hashlock() {
+	new_test_ext().execute_with(|| {
+		MinTimelockMargin::set(&3);
+		let hash = hashing::sha2_256(b"Something between us 2023");
+		let lock = |sender: AccountId, recipient: AccountId, timelock: u64| {
+			Aswap::lock(
+				RuntimeOrigin::signed(sender),
+				Some(hashing::sha2_256(&(sender, recipient, hash, timelock).encode())),
+				recipient,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				1_000,
+			)
+		};
+
+		//the initiator's lock expires at block 11
+		assert_ok!(lock(ACCOUNT_A, ACCOUNT_B, 10));
+		//a third party locking under the public hashlock doesn't affect the answer
+		assert_ok!(lock(ACCOUNT_C, ACCOUNT_B, 1));
+		assert_ok!(lock(ACCOUNT_C, ACCOUNT_A, 1));
+		//the answering lock must expire at block 8 or earlier
+		assert_noop!(lock(ACCOUNT_B, ACCOUNT_A, 8), Error::<Test>::TimelockMarginTooShort);
+		assert_ok!(lock(ACCOUNT_B, ACCOUNT_A, 7));
+		//other hashlocks are not affected
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_B),
+			Some([7u8; 32]),
+			ACCOUNT_A,
+			[7u8; 32],
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(10),
+			ASSET_A,
+			1_000
+		));
+
+		//every pending lock of the initiator is checked, also past `MaxOpenLocksPerAccount`
+		MaxOpenLocksPerAccount::set(&1);
+		for who in [ACCOUNT_A, ACCOUNT_B] {
+			assert_ok!(Aswap::set_open_lock_limit(RuntimeOrigin::root(), who, Some(10)));
+		}
+		for n in 0..5u8 {
+			assert_ok!(Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some([n; 32]),
+				ACCOUNT_C,
+				[n; 32],
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(10),
+				ASSET_A,
+				1_000
+			));
+		}
+		assert_noop!(lock(ACCOUNT_B, ACCOUNT_A, 8), Error::<Test>::TimelockMarginTooShort);
+	});
+}
+
+#[test]
+fn time_based_expiry() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		let tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash).encode());
+		let lock = |until| {
+			Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Until(until),
+				ASSET_A,
+				asset_amount,
+			)
+		};
+
+		//the timestamp must be in the future
+		assert_noop!(lock(Timestamp::now()), Error::<Test>::InvalidTimelock);
+		//5 blocks
+		let until = Timestamp::now() + 5 * ExpectedBlockTime::get();
+		assert_ok!(lock(until));
+		let lock_details = Aswap::lock_transactions(tx_id).unwrap();
+		assert_eq!(lock_details.expiry, Expiry::AtTime(until));
+		assert_eq!(lock_details.expiration_block, 6);
+		assert_noop!(
+			Aswap::cancel(RuntimeOrigin::signed(ACCOUNT_A), tx_id),
+			Error::<Test>::TimeLockNotExpired
+		);
+
+		//blocks are produced faster than expected, so the lock is checked again later
+		ExpectedBlockTime::set(3_000);
+		run_to_block(6);
+		assert_eq!(Aswap::lock_status(tx_id), Some(LockStatus::Pending));
+		assert!(ExpiringLocks::<Test>::contains_key(12, tx_id));
+
+		run_to_block(12);
+		assert_eq!(Aswap::lock_status(tx_id), Some(LockStatus::Refunded));
+		assert_eq!(get_account_balance(ACCOUNT_A, ASSET_A), ACCOUNTS_START_BALANCE);
+	});
+}
+
+#[test]
+fn derived_tx_ids() {
+	new_test_ext().execute_with(|| {
+		let hash = hashing::sha2_256(b"Something between us 2023");
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let lock = |tx_id| {
+			Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				tx_id,
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				asset_amount,
+			)
+		};
+
+		assert_noop!(lock(None), Error::<Test>::TransactionIdRequired);
+
+		DeriveTxIds::set(true);
+		assert_noop!(lock(Some([1u8; 32])), Error::<Test>::TransactionIdNotAllowed);
+
+		//the same parameters get a new id for every lock
+		let expiry = Expiry::AtBlock(1 + timelock);
+		let mut tx_ids = Vec::new();
+		for nonce in 0..2 {
+			assert_eq!(Aswap::lock_nonces(ACCOUNT_A), nonce);
+			let tx_id = Aswap::derive_tx_id(
+				&ACCOUNT_A,
+				&ACCOUNT_B,
+				hash,
+				SwapAsset::Asset(ASSET_A),
+				asset_amount,
+				&expiry,
+			);
+			assert_ok!(lock(None));
+			System::assert_last_event(
+				crate::Event::Locked {
+					tx_id,
+					sender: ACCOUNT_A,
+					recipient: ACCOUNT_B,
+					hashlock: hash,
+					hash_algorithm: HashAlgorithm::Sha2_256,
+					expiry,
+					asset: SwapAsset::Asset(ASSET_A),
+					asset_amount,
+					deposit: 0,
+				}
+				.into(),
+			);
+			assert_eq!(Aswap::lock_transactions(tx_id).unwrap().sender, ACCOUNT_A);
+			tx_ids.push(tx_id);
+		}
+		assert_ne!(tx_ids[0], tx_ids[1]);
+		assert_eq!(Aswap::lock_nonces(ACCOUNT_A), 2);
+	});
+}
+
+#[test]
+fn lock_events_are_indexed_by_hashlock() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let tx_ids: Vec<[u8; 32]> = (0..2u32)
+			.map(|index| hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash, index).encode()))
+			.collect();
+		for tx_id in tx_ids.iter() {
+			assert_ok!(Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(*tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				asset_amount
+			));
+		}
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_ids[0], secret.to_vec()));
+		run_to_block(timelock + 1);
+		assert_ok!(Aswap::cancel(RuntimeOrigin::signed(ACCOUNT_A), tx_ids[1]));
+
+		//the secret can be picked up from the events of the hashlock
+		let topic = Aswap::topic(hash);
+		let events: Vec<_> = System::events()
+			.into_iter()
+			.filter(|record| record.topics.contains(&topic))
+			.map(|record| record.event)
+			.collect();
+		assert_eq!(events.len(), 4);
+		assert_eq!(
+			events[2],
+			crate::Event::Unlocked {
+				tx_id: tx_ids[0],
+				sender: ACCOUNT_A,
+				recipient: ACCOUNT_B,
+				asset: SwapAsset::Asset(ASSET_A),
+				asset_amount,
+				fee: 0,
+				preimage: secret.to_vec().try_into().unwrap(),
+			}
+			.into()
+		);
+		assert_eq!(
+			events[3],
+			crate::Event::Canceled {
+				tx_id: tx_ids[1],
+				sender: ACCOUNT_A,
+				recipient: ACCOUNT_B,
+				asset: SwapAsset::Asset(ASSET_A),
+				asset_amount,
+			}
+			.into()
+		);
+		assert_eq!(System::event_topics(Aswap::topic(tx_ids[1])).len(), 2);
+	});
+}
+
+#[test]
+fn settled_locks_are_pruned() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		RetentionPeriod::set(10);
+		let tx_ids: Vec<[u8; 32]> = (0..2u32)
+			.map(|index| hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash, index).encode()))
+			.collect();
+		let lock = |tx_id| {
+			Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				asset_amount,
+			)
+		};
+		for tx_id in tx_ids.iter() {
+			assert_ok!(lock(*tx_id));
+		}
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_ids[0], secret.to_vec()));
+		assert_noop!(
+			Aswap::reap(RuntimeOrigin::signed(ACCOUNT_C), tx_ids[0]),
+			Error::<Test>::RetentionNotElapsed
+		);
+		assert_noop!(
+			Aswap::reap(RuntimeOrigin::signed(ACCOUNT_C), tx_ids[1]),
+			Error::<Test>::SwapPending
+		);
+
+		//secrets the v5 migration kept out of `KnownSecrets` go with their lock
+		OversizedSecrets::<Test>::insert(tx_ids[0], secret.to_vec().try_into().unwrap());
+
+		//the second lock is refunded at block 6
+		run_to_block(11);
+		Aswap::on_idle(11, Weight::MAX);
+		assert!(Aswap::lock_transactions(tx_ids[0]).is_none());
+		assert!(Aswap::known_secrets(tx_ids[0]).is_none());
+		assert!(Aswap::oversized_secrets(tx_ids[0]).is_none());
+		System::assert_has_event(crate::Event::Pruned { tx_id: tx_ids[0] }.into());
+		assert!(Aswap::lock_transactions(tx_ids[1]).is_some());
+
+		//pruned ids can't be used again
+		assert_noop!(lock(tx_ids[0]), Error::<Test>::TransactionIdExists);
+
+		//anyone can reap settled locks for free
+		run_to_block(16);
+		let post_info = Aswap::reap(RuntimeOrigin::signed(ACCOUNT_C), tx_ids[1]).unwrap();
+		assert_eq!(post_info.pays_fee, Pays::No);
+		assert!(Aswap::lock_transactions(tx_ids[1]).is_none());
+	});
+}
+
+#[test]
+fn lock_storage_deposit() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		DepositBase::set(100);
+		DepositPerByte::set(1);
+		RetentionPeriod::set(10);
+		let tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash).encode());
+		let lock_size = LockDetailsOf::<Test>::max_encoded_len() as u128;
+		let deposit = 100 + lock_size + PreimageOf::<Test>::max_encoded_len() as u128;
+
+		assert_noop!(
+			Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_D_LOW_BALANCES),
+				Some(tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				1
+			),
+			Error::<Test>::InsufficientDeposit
+		);
+
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(tx_id),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			ASSET_A,
+			asset_amount
+		));
+		assert_eq!(Balances::reserved_balance(ACCOUNT_A), deposit);
+		assert_eq!(Aswap::lock_transactions(tx_id).unwrap().deposit, deposit);
+
+		//the deposit of the secret only covers its size once revealed
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()));
+		let held = 100 + lock_size + secret.len() as u128 + 1;
+		assert_eq!(Balances::reserved_balance(ACCOUNT_A), held);
+		System::assert_has_event(
+			crate::Event::DepositReleased { tx_id, who: ACCOUNT_A, amount: deposit - held }.into(),
+		);
+
+		//the rest is released when the lock is pruned
+		run_to_block(11);
+		assert_ok!(Aswap::reap(RuntimeOrigin::signed(ACCOUNT_C), tx_id));
+		assert_eq!(Balances::reserved_balance(ACCOUNT_A), 0);
+		assert_eq!(get_native_balance(ACCOUNT_A), ACCOUNTS_START_BALANCE);
+		System::assert_has_event(
+			crate::Event::DepositReleased { tx_id, who: ACCOUNT_A, amount: held }.into(),
+		);
+	});
+}
+
+#[test]
+fn native_locks_leave_room_for_deposits() {
+	new_test_ext().execute_with(|| {
+		let hash = hashing::sha2_256(b"Something between us 2023");
+		//5 blocks
+		let timelock = 5;
+		DepositBase::set(100);
+		DepositPerByte::set(1);
+		KeepSenderAlive::set(false);
+		let deposit = Aswap::deposit_for(PreimageOf::<Test>::max_encoded_len());
+		let lock_native = |amount: u128| {
+			Aswap::lock_native(
+				RuntimeOrigin::signed(ACCOUNT_C),
+				Some(hashing::sha2_256(&(ACCOUNT_C, amount).encode())),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				amount,
+			)
+		};
+		let free = get_native_balance(ACCOUNT_C);
+		let existential_deposit: u128 = ExistentialDeposit::get();
+
+		//the storage deposit is checked with the amount, not left to the reserve
+		assert_noop!(lock_native(free), Error::<Test>::InsufficientDeposit);
+		//sub-accounts also need the existential deposit of the escrow
+		LockCustody::set(Custody::SubAccount);
+		assert_noop!(lock_native(free - deposit), Error::<Test>::InsufficientDeposit);
+		assert_ok!(lock_native(free - deposit - existential_deposit));
+		assert_eq!(get_native_balance(ACCOUNT_C), 0);
+		assert_eq!(Balances::reserved_balance(ACCOUNT_C), deposit);
+	});
+}
+
+#[test]
+fn open_locks_are_limited_per_account() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		//5 blocks
+		let timelock = 5;
+		MaxOpenLocksPerAccount::set(2);
+		let tx_ids: Vec<[u8; 32]> = (0u8..3).map(|index| [index; 32]).collect();
+		let lock = |tx_id: [u8; 32]| {
+			Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				100,
+			)
+		};
+
+		assert_ok!(lock(tx_ids[0]));
+		assert_ok!(lock(tx_ids[1]));
+		assert_eq!(Aswap::open_lock_count(ACCOUNT_A), 2);
+		assert_noop!(lock(tx_ids[2]), Error::<Test>::TooManyOpenLocks);
+
+		//settled locks are no longer open
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_ids[0], secret.to_vec()));
+		assert_eq!(Aswap::open_lock_count(ACCOUNT_A), 1);
+		assert_ok!(lock(tx_ids[2]));
+		System::set_block_number(10);
+		assert_ok!(Aswap::cancel(RuntimeOrigin::signed(ACCOUNT_A), tx_ids[1]));
+		assert_eq!(Aswap::open_lock_count(ACCOUNT_A), 1);
+
+		//governance sets a higher limit for a market maker
+		assert_noop!(
+			Aswap::set_open_lock_limit(RuntimeOrigin::signed(ACCOUNT_A), ACCOUNT_A, Some(3)),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Aswap::set_open_lock_limit(RuntimeOrigin::root(), ACCOUNT_A, Some(3)));
+		System::assert_last_event(
+			crate::Event::OpenLockLimitSet { who: ACCOUNT_A, limit: Some(3) }.into(),
+		);
+		assert_eq!(Aswap::open_lock_limit(&ACCOUNT_A), 3);
+		assert_ok!(lock([3; 32]));
+		assert_ok!(lock([4; 32]));
+		assert_noop!(lock([5; 32]), Error::<Test>::TooManyOpenLocks);
+
+		assert_ok!(Aswap::set_open_lock_limit(RuntimeOrigin::root(), ACCOUNT_A, None));
+		assert_eq!(Aswap::open_lock_limit(&ACCOUNT_A), 2);
+	});
+}
+
+#[test]
+fn held_locks_stay_in_sender_account() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		LockCustody::set(Custody::Hold);
+		let tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash).encode());
+		let native_tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_C, hash).encode());
+
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(tx_id),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			ASSET_A,
+			asset_amount
+		));
+		assert_eq!(Aswap::lock_transactions(tx_id).unwrap().custody, Custody::Hold);
+		assert_eq!(get_pallet_balance(ASSET_A), PALLET_START_BALANCE);
+		assert_eq!(get_account_balance(ACCOUNT_A, ASSET_A), ACCOUNTS_START_BALANCE);
+		assert_eq!(Aswap::held_assets(ASSET_A, ACCOUNT_A), asset_amount);
+		//held funds can't be moved by the sender
+		assert!(Assets::transfer(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			ASSET_A.into(),
+			ACCOUNT_C,
+			ACCOUNTS_START_BALANCE - asset_amount + 1
+		)
+		.is_err());
+
+		assert_ok!(Aswap::lock_native(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(native_tx_id),
+			ACCOUNT_C,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			asset_amount
+		));
+		assert_eq!(Balances::reserved_balance_named(&Aswap::hold_id(), &ACCOUNT_A), asset_amount);
+		assert_eq!(get_native_balance(ACCOUNT_A), ACCOUNTS_START_BALANCE - asset_amount);
+
+		//unlock moves the held funds to the recipient
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()));
+		assert_eq!(get_account_balance(ACCOUNT_A, ASSET_A), ACCOUNTS_START_BALANCE - asset_amount);
+		assert_eq!(get_account_balance(ACCOUNT_B, ASSET_A), ACCOUNTS_START_BALANCE + asset_amount);
+		assert_eq!(Aswap::held_assets(ASSET_A, ACCOUNT_A), 0);
+
+		//cancel releases them to the sender
+		System::set_block_number(10);
+		assert_ok!(Aswap::cancel(RuntimeOrigin::signed(ACCOUNT_A), native_tx_id));
+		assert_eq!(Balances::reserved_balance_named(&Aswap::hold_id(), &ACCOUNT_A), 0);
+		assert_eq!(get_native_balance(ACCOUNT_A), ACCOUNTS_START_BALANCE);
+	});
+}
+
+#[test]
+fn sub_account_escrow() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		LockCustody::set(Custody::SubAccount);
+		let tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_B, hash).encode());
+		let native_tx_id = hashing::sha2_256(&(ACCOUNT_A, ACCOUNT_C, hash).encode());
+		let escrow = Aswap::escrow_account(tx_id);
+		let native_escrow = Aswap::escrow_account(native_tx_id);
+		assert_ne!(escrow, native_escrow);
+		assert_ne!(escrow, Aswap::account_id());
+		//ids sharing a prefix don't share an escrow
+		let mut twin_tx_id = tx_id;
+		twin_tx_id[31] ^= 1;
+		assert_ne!(Aswap::escrow_account(twin_tx_id), escrow);
+
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(tx_id),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			ASSET_A,
+			asset_amount
+		));
+		assert_ok!(Aswap::lock_native(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(native_tx_id),
+			ACCOUNT_C,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			asset_amount
+		));
+		//each swap is escrowed in its own account, kept alive by the sender
+		let existential_deposit: u128 = ExistentialDeposit::get();
+		assert_eq!(get_pallet_balance(ASSET_A), PALLET_START_BALANCE);
+		assert_eq!(get_account_balance(escrow, ASSET_A), asset_amount);
+		assert_eq!(get_native_balance(escrow), existential_deposit);
+		assert_eq!(get_native_balance(native_escrow), existential_deposit + asset_amount);
+		assert_eq!(
+			get_native_balance(ACCOUNT_A),
+			ACCOUNTS_START_BALANCE - asset_amount - 2 * existential_deposit
+		);
+
+		//settling a swap reaps its escrow account
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()));
+		assert_eq!(get_account_balance(ACCOUNT_B, ASSET_A), ACCOUNTS_START_BALANCE + asset_amount);
+		assert!(!System::account_exists(&escrow));
+		System::set_block_number(10);
+		assert_ok!(Aswap::cancel(RuntimeOrigin::signed(ACCOUNT_A), native_tx_id));
+		assert!(!System::account_exists(&native_escrow));
+		assert_eq!(get_native_balance(ACCOUNT_A), ACCOUNTS_START_BALANCE);
+	});
+}
+
+#[test]
+fn sub_account_escrow_kept_alive_by_another_asset() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let foreign_asset = 300;
+		LockCustody::set(Custody::SubAccount);
+		let (tx_id, native_tx_id) = ([1; 32], [2; 32]);
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(tx_id),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			ASSET_A,
+			asset_amount
+		));
+		assert_ok!(Aswap::lock_native(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(native_tx_id),
+			ACCOUNT_C,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			asset_amount
+		));
+
+		//anyone can give the escrows a consumer with an asset that isn't sufficient
+		assert_ok!(Assets::create(
+			RuntimeOrigin::signed(ACCOUNT_C),
+			foreign_asset.into(),
+			ACCOUNT_C,
+			1
+		));
+		assert_ok!(Assets::mint(
+			RuntimeOrigin::signed(ACCOUNT_C),
+			foreign_asset.into(),
+			ACCOUNT_C,
+			10
+		));
+		for tx_id in [tx_id, native_tx_id] {
+			assert_ok!(Assets::transfer(
+				RuntimeOrigin::signed(ACCOUNT_C),
+				foreign_asset.into(),
+				Aswap::escrow_account(tx_id),
+				1
+			));
+		}
+
+		//the swaps still settle, leaving the existential deposit in the escrows
+		let existential_deposit: u128 = ExistentialDeposit::get();
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()));
+		assert_eq!(get_account_balance(ACCOUNT_B, ASSET_A), ACCOUNTS_START_BALANCE + asset_amount);
+		assert_eq!(get_native_balance(Aswap::escrow_account(tx_id)), existential_deposit);
+		System::set_block_number(10);
+		assert_ok!(Aswap::cancel(RuntimeOrigin::signed(ACCOUNT_A), native_tx_id));
+		assert_eq!(get_native_balance(Aswap::escrow_account(native_tx_id)), existential_deposit);
+		assert_eq!(get_native_balance(ACCOUNT_A), ACCOUNTS_START_BALANCE - 2 * existential_deposit);
+	});
+}
+
+#[test]
+fn custody_audit() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let asset = SwapAsset::Asset(ASSET_A);
+
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some([1; 32]),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			ASSET_A,
+			asset_amount
+		));
+		LockCustody::set(Custody::Hold);
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_C),
+			Some([2; 32]),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			ASSET_A,
+			asset_amount
+		));
+		LockCustody::set(Custody::SubAccount);
+		assert_ok!(Aswap::lock_native(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some([3; 32]),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			asset_amount
+		));
+
+		let audit = Aswap::audit(asset);
+		assert_eq!(audit.total_locked, 2 * asset_amount);
+		assert_eq!(audit.expected, 2 * asset_amount);
+		assert_eq!(audit.actual, PALLET_START_BALANCE + 2 * asset_amount);
+		assert!(audit.is_solvent());
+		assert!(Aswap::audit(SwapAsset::Native).is_solvent());
+		assert_ok!(Aswap::do_try_state());
+
+		//settled locks leave the audit
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), [1; 32], secret.to_vec()));
+		let audit = Aswap::audit(asset);
+		assert_eq!((audit.total_locked, audit.expected), (asset_amount, asset_amount));
+		assert_ok!(Aswap::do_try_state());
+
+		//funds missing from custody are reported
+		TotalLocked::<Test>::insert(asset, asset_amount + 1);
+		assert!(!Aswap::audit(asset).is_solvent());
+		assert_eq!(Aswap::do_try_state(), Err("aswap: asset is not solvent"));
+		TotalLocked::<Test>::insert(asset, asset_amount);
+		LocksBySender::<Test>::remove(ACCOUNT_C, [2; 32]);
+		assert_eq!(Aswap::do_try_state(), Err("aswap: pending lock missing from an index"));
+
+		//held assets only count up to the balance of their holder
+		let inflated = PALLET_START_BALANCE + ACCOUNTS_START_BALANCE + 1;
+		LockTransactions::<Test>::mutate([2; 32], |lock| lock.as_mut().unwrap().amount = inflated);
+		HeldAssets::<Test>::insert(ASSET_A, ACCOUNT_C, inflated);
+		TotalLocked::<Test>::insert(asset, inflated);
+		let audit = Aswap::audit(asset);
+		assert_eq!(audit.actual, PALLET_START_BALANCE + ACCOUNTS_START_BALANCE);
+		assert!(!audit.is_solvent());
+	});
+}
+
+#[test]
+fn lock_checks_sender_and_recipient_balances() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		//5 blocks
+		let timelock = 5;
+		let asset_id = 200;
+		let min_balance = 10;
+		let account_without_balance = 99;
+		assert_ok!(Assets::force_create(
+			RuntimeOrigin::root(),
+			asset_id.into(),
+			ACCOUNT_A,
+			false,
+			min_balance
+		));
+		assert_ok!(Assets::mint(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			asset_id.into(),
+			ACCOUNT_A,
+			1_000
+		));
+		let lock = |recipient: AccountId, amount: u128| {
+			Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(hashing::sha2_256(&(recipient, amount).encode())),
+				recipient,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				asset_id,
+				amount,
+			)
+		};
+
+		//recipient couldn't unlock the funds
+		assert_noop!(lock(ACCOUNT_B, min_balance - 1), Error::<Test>::RecipientBelowMinimum);
+		assert_noop!(lock(account_without_balance, 100), Error::<Test>::RecipientCannotCreate);
+
+		//full balance locks depend on `KeepSenderAlive`
+		assert_noop!(lock(ACCOUNT_B, 1_000), Error::<Test>::WouldReapSender);
+		KeepSenderAlive::set(false);
+		assert_ok!(lock(ACCOUNT_B, 1_000));
+		assert_eq!(get_account_balance(ACCOUNT_A, asset_id), 0);
+
+		//frozen accounts can't lock
+		assert_ok!(Assets::mint(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			asset_id.into(),
+			ACCOUNT_A,
+			1_000
+		));
+		assert_ok!(Assets::freeze(RuntimeOrigin::signed(ACCOUNT_A), asset_id.into(), ACCOUNT_A));
+		assert_noop!(lock(ACCOUNT_C, 100), Error::<Test>::BalanceFrozen);
+	});
+}
+
+#[test]
+fn unlock_charges_fee() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let policy =
+			FeePolicy { rate: Permill::from_percent(10), flat: 5, scale_by_duration: false };
+		let lock = |tx_id: [u8; 32]| {
+			Aswap::lock(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				Some(tx_id),
+				ACCOUNT_B,
+				hash,
+				HashAlgorithm::Sha2_256,
+				Timelock::Blocks(timelock),
+				ASSET_A,
+				asset_amount,
+			)
+		};
+
+		assert_noop!(
+			Aswap::set_fee_policy(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				SwapAsset::Asset(ASSET_A),
+				Some(policy)
+			),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Aswap::set_fee_policy(
+			RuntimeOrigin::root(),
+			SwapAsset::Asset(ASSET_A),
+			Some(policy)
+		));
+		System::assert_last_event(
+			crate::Event::FeePolicySet { asset: SwapAsset::Asset(ASSET_A), policy: Some(policy) }
+				.into(),
+		);
+		assert_ok!(lock([1; 32]));
+		assert_ok!(lock([2; 32]));
+		assert_eq!(Aswap::lock_transactions([1; 32]).unwrap().fee, 105);
+
+		//pending locks keep the fee they were created with
+		assert_ok!(Aswap::set_fee_policy(RuntimeOrigin::root(), SwapAsset::Asset(ASSET_A), None));
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), [1; 32], secret.to_vec()));
+		assert_eq!(get_account_balance(ACCOUNT_B, ASSET_A), ACCOUNTS_START_BALANCE + 895);
+		assert_eq!(get_account_balance(ACCOUNT_FEES, ASSET_A), 105);
+		System::assert_last_event(
+			crate::Event::Unlocked {
+				tx_id: [1; 32],
+				sender: ACCOUNT_A,
+				recipient: ACCOUNT_B,
+				asset: SwapAsset::Asset(ASSET_A),
+				asset_amount,
+				fee: 105,
+				preimage: secret.to_vec().try_into().unwrap(),
+			}
+			.into(),
+		);
+
+		//refunds are free
+		System::set_block_number(10);
+		assert_ok!(Aswap::cancel(RuntimeOrigin::signed(ACCOUNT_A), [2; 32]));
+		assert_eq!(get_account_balance(ACCOUNT_A, ASSET_A), ACCOUNTS_START_BALANCE - asset_amount);
+		assert_eq!(get_account_balance(ACCOUNT_FEES, ASSET_A), 105);
+
+		//fees can scale with the duration of the lock, up to `MaxTimelock`
+		let policy =
+			FeePolicy { rate: Permill::from_percent(10), flat: 0, scale_by_duration: true };
+		assert_ok!(Aswap::set_fee_policy(RuntimeOrigin::root(), SwapAsset::Native, Some(policy)));
+		assert_eq!(Aswap::fee_for(SwapAsset::Native, asset_amount, MaxTimelock::get() / 2), 50);
+		assert_eq!(Aswap::fee_for(SwapAsset::Native, asset_amount, MaxTimelock::get() * 2), 100);
+		assert_eq!(Aswap::fee_for(SwapAsset::Asset(ASSET_A), asset_amount, timelock), 0);
+	});
+}
+
+#[test]
+fn fee_the_destination_cannot_receive_goes_to_recipient() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let asset_id = 200;
+		let min_balance = 10;
+		assert_ok!(Assets::force_create(
+			RuntimeOrigin::root(),
+			asset_id.into(),
+			ACCOUNT_A,
+			false,
+			min_balance
+		));
+		for (who, amount) in [
+			(ACCOUNT_A, 2 * asset_amount),
+			(ACCOUNT_B, min_balance),
+			(Aswap::account_id(), min_balance),
+		] {
+			assert_ok!(Assets::mint(
+				RuntimeOrigin::signed(ACCOUNT_A),
+				asset_id.into(),
+				who,
+				amount
+			));
+		}
+		//the fee destination has no account of the asset and the fee is below its minimum
+		let policy = FeePolicy { rate: Permill::zero(), flat: 5, scale_by_duration: false };
+		assert_ok!(Aswap::set_fee_policy(
+			RuntimeOrigin::root(),
+			SwapAsset::Asset(asset_id),
+			Some(policy)
+		));
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some([1; 32]),
+			ACCOUNT_B,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			asset_id,
+			asset_amount
+		));
+		assert_eq!(Aswap::lock_transactions([1; 32]).unwrap().fee, 0);
+
+		assert_ok!(Aswap::unlock(RuntimeOrigin::signed(ACCOUNT_B), [1; 32], secret.to_vec()));
+		assert_eq!(get_account_balance(ACCOUNT_B, asset_id), min_balance + asset_amount);
+		assert_eq!(get_account_balance(ACCOUNT_FEES, asset_id), 0);
+	});
+}
+
+#[test]
+fn relayer_unlocks_for_recipient() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		let relayer_fee = 100;
+		//5 blocks
+		let timelock = 5;
+		let tx_id = [1; 32];
+		assert_eq!(get_native_balance(ACCOUNT_E_NO_NATIVE), 0);
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(tx_id),
+			ACCOUNT_E_NO_NATIVE,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			ASSET_A,
+			asset_amount
+		));
+		let sign = |signer: AccountId, relayer_fee: u128| {
+			MockSignature(signer, Aswap::unlock_for_payload(tx_id, relayer_fee))
+		};
+		let unlock_for = |signature: MockSignature, relayer_fee: u128| {
+			Aswap::unlock_for(
+				RuntimeOrigin::signed(ACCOUNT_C),
+				tx_id,
+				secret.to_vec(),
+				signature,
+				relayer_fee,
+			)
+		};
+
+		//the recipient must sign the claim and the fee it pays
+		assert_noop!(
+			unlock_for(sign(ACCOUNT_C, relayer_fee), relayer_fee),
+			Error::<Test>::InvalidSignature
+		);
+		assert_noop!(
+			unlock_for(sign(ACCOUNT_E_NO_NATIVE, relayer_fee), relayer_fee + 1),
+			Error::<Test>::InvalidSignature
+		);
+		//signatures over the same fields without the domain tag don't authorise a claim
+		let untagged = (tx_id, relayer_fee, System::block_hash(0)).encode();
+		assert_noop!(
+			unlock_for(MockSignature(ACCOUNT_E_NO_NATIVE, untagged), relayer_fee),
+			Error::<Test>::InvalidSignature
+		);
+		assert_noop!(
+			unlock_for(sign(ACCOUNT_E_NO_NATIVE, asset_amount + 1), asset_amount + 1),
+			Error::<Test>::RelayerFeeTooHigh
+		);
+		assert_noop!(
+			Aswap::unlock_for(
+				RuntimeOrigin::signed(ACCOUNT_C),
+				tx_id,
+				b"wrong secret".to_vec(),
+				sign(ACCOUNT_E_NO_NATIVE, relayer_fee),
+				relayer_fee,
+			),
+			Error::<Test>::InvalidPreimage
+		);
+
+		assert_ok!(unlock_for(sign(ACCOUNT_E_NO_NATIVE, relayer_fee), relayer_fee));
+		assert_eq!(get_account_balance(ACCOUNT_E_NO_NATIVE, ASSET_A), asset_amount - relayer_fee);
+		assert_eq!(get_account_balance(ACCOUNT_C, ASSET_A), ACCOUNTS_START_BALANCE + relayer_fee);
+		System::assert_last_event(
+			crate::Event::RelayerPaid { tx_id, relayer: ACCOUNT_C, fee: relayer_fee }.into(),
+		);
+		assert_noop!(
+			unlock_for(sign(ACCOUNT_E_NO_NATIVE, relayer_fee), relayer_fee),
+			Error::<Test>::AlreadyWithdrawn
+		);
+	});
+}
+
+#[test]
+fn unsigned_unlock_is_validated_by_the_preimage() {
+	new_test_ext().execute_with(|| {
+		let secret = b"Something between us 2023";
+		let hash = hashing::sha2_256(secret);
+		let asset_amount = 1_000;
+		//5 blocks
+		let timelock = 5;
+		let tx_id = [1; 32];
+		assert_ok!(Aswap::lock(
+			RuntimeOrigin::signed(ACCOUNT_A),
+			Some(tx_id),
+			ACCOUNT_E_NO_NATIVE,
+			hash,
+			HashAlgorithm::Sha2_256,
+			Timelock::Blocks(timelock),
+			ASSET_A,
+			asset_amount
+		));
+		let validate = |tx_id: [u8; 32], preimage: &[u8]| -> TransactionValidity {
+			Aswap::validate_unsigned(
+				TransactionSource::External,
+				&crate::Call::unlock_unsigned { tx_id, preimage: preimage.to_vec() },
+			)
+		};
+
+		//only valid preimages of pending locks enter the pool
+		assert_eq!(validate([2; 32], secret), Err(InvalidTransaction::Stale.into()));
+		assert_eq!(validate(tx_id, b"wrong secret"), Err(InvalidTransaction::BadProof.into()));
+		assert_eq!(validate(tx_id, &[1u8; 257]), Err(InvalidTransaction::ExhaustsResources.into()));
+		let validity = validate(tx_id, secret).unwrap();
+		assert_eq!(validity.longevity, timelock);
+		assert_eq!(validity.priority, UnsignedPriority::get() + MaxTimelock::get() - timelock);
+		run_to_block(3);
+		assert!(validate(tx_id, secret).unwrap().priority > validity.priority);
+
+		//the recipient claims without holding any balance
+		assert_noop!(
+			Aswap::unlock_unsigned(RuntimeOrigin::signed(ACCOUNT_B), tx_id, secret.to_vec()),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_eq!(get_native_balance(ACCOUNT_E_NO_NATIVE), 0);
+		assert_ok!(Aswap::unlock_unsigned(RuntimeOrigin::none(), tx_id, secret.to_vec()));
+		assert_eq!(get_account_balance(ACCOUNT_E_NO_NATIVE, ASSET_A), asset_amount);
+		assert_eq!(validate(tx_id, secret), Err(InvalidTransaction::Stale.into()));
+	});
+}
+
+/// Runs a migration the way try-runtime does, checking its `pre_upgrade`/`post_upgrade` hooks
+/// when they are compiled in.
+fn run_migration<M: OnRuntimeUpgrade>() {
+	#[cfg(feature = "try-runtime")]
+	let state = M::pre_upgrade().unwrap();
+	M::on_runtime_upgrade();
+	#[cfg(feature = "try-runtime")]
+	M::post_upgrade(state).unwrap();
+}
+
+#[test]
+fn migrate_lock_flags_to_status() {
+	new_test_ext().execute_with(|| {
+		let hash = hashing::sha2_256(b"Something between us 2023");
+		let v2_lock = |tx_id, is_withdraw, is_refunded| -> migrations::v2::LockDetailsV2Of<Test> {
+			migrations::v2::LockDetailsV2 {
+				tx_id,
+				sender: ACCOUNT_A,
+				recipient: ACCOUNT_B,
+				asset: SwapAsset::Asset(ASSET_A),
+				amount: 1_000,
+				hashlock: hash,
+				hash_algorithm: HashAlgorithm::Sha2_256,
+				expiration_block: 6,
+				is_withdraw,
+				is_refunded,
+			}
+		};
+		let (pending, claimed, refunded) = ([1; 32], [2; 32], [3; 32]);
+		for (tx_id, is_withdraw, is_refunded) in
+			[(pending, false, false), (claimed, true, false), (refunded, false, true)]
+		{
+			migrations::v2::LockTransactions::<Test>::insert(
+				tx_id,
+				v2_lock(tx_id, is_withdraw, is_refunded),
+			);
+		}
+		StorageVersion::new(3).put::<Aswap>();
+
+		//only the pending lock is backfilled into the indexes
+		run_migration::<migrations::v4::MigrateToV4<Test>>();
+		assert_eq!(StorageVersion::get::<Aswap>(), StorageVersion::new(4));
+		assert!(LocksBySender::<Test>::contains_key(ACCOUNT_A, pending));
+		assert!(LocksByRecipient::<Test>::contains_key(ACCOUNT_B, pending));
+		assert!(LocksByHashlock::<Test>::contains_key(hash, pending));
+		for tx_id in [claimed, refunded] {
+			assert!(!LocksBySender::<Test>::contains_key(ACCOUNT_A, tx_id));
+			assert!(!LocksByRecipient::<Test>::contains_key(ACCOUNT_B, tx_id));
+			assert!(!LocksByHashlock::<Test>::contains_key(hash, tx_id));
+		}
+
+		//settled locks record the block of the upgrade
+		run_migration::<migrations::v5::MigrateToV5<Test>>();
+		System::set_block_number(10);
+		run_migration::<migrations::v6::MigrateToV6<Test>>();
+		assert_eq!(StorageVersion::get::<Aswap>(), StorageVersion::new(6));
+		let status =
+			|tx_id: [u8; 32]| migrations::v6::LockTransactions::<Test>::get(tx_id).unwrap().status;
+		assert_eq!(status(pending), SwapStatus::Pending);
+		assert_eq!(status(claimed), SwapStatus::Claimed { at: 10 });
+		assert_eq!(status(refunded), SwapStatus::Refunded { at: 10 });
+		assert_eq!(migrations::v6::LockTransactions::<Test>::iter_keys().count(), 3);
+
+		//migrations only run from the version they expect
+		migrations::v6::MigrateToV6::<Test>::on_runtime_upgrade();
+		assert_eq!(status(claimed), SwapStatus::Claimed { at: 10 });
+	});
+}
+
+#[test]
+fn migrate_oversized_secrets() {
+	new_test_ext().execute_with(|| {
+		let max_len = MaxPreimageLen::get() as usize;
+		let (bounded, oversized) = ([1; 32], [2; 32]);
+		migrations::v5::old::KnownSecrets::<Test>::insert(bounded, vec![1; max_len]);
+		migrations::v5::old::KnownSecrets::<Test>::insert(oversized, vec![2; max_len + 1]);
+		StorageVersion::new(4).put::<Aswap>();
+
+		run_migration::<migrations::v5::MigrateToV5<Test>>();
+		assert_eq!(StorageVersion::get::<Aswap>(), StorageVersion::new(5));
+		assert_eq!(Aswap::known_secrets(bounded).unwrap().into_inner(), vec![1; max_len]);
+		assert!(Aswap::oversized_secrets(bounded).is_none());
+
+		//secrets over the bound are kept readable outside of `KnownSecrets`
+		assert!(Aswap::known_secrets(oversized).is_none());
+		assert_eq!(Aswap::oversized_secrets(oversized).unwrap().into_inner(), vec![2; max_len + 1]);
+	});
+}
+
+#[test]
+fn migration_refuses_secrets_over_the_legacy_bound() {
+	new_test_ext().execute_with(|| {
+		let max_len = MaxLegacyPreimageLen::get() as usize;
+		migrations::v5::old::KnownSecrets::<Test>::insert([1; 32], vec![1; max_len + 1]);
+		StorageVersion::new(4).put::<Aswap>();
+
+		//try-runtime stops the upgrade so the bound can be raised first
+		#[cfg(feature = "try-runtime")]
+		assert!(migrations::v5::MigrateToV5::<Test>::pre_upgrade().is_err());
+		migrations::v5::MigrateToV5::<Test>::on_runtime_upgrade();
+		assert!(Aswap::known_secrets([1; 32]).is_none());
+		assert!(Aswap::oversized_secrets([1; 32]).is_none());
+	});
+}
+
+#[test]
+fn migrate_settled_locks_start_pruning_at_oldest() {
+	new_test_ext().execute_with(|| {
+		let v8_lock = |tx_id, status| -> migrations::v8::LockDetailsV8Of<Test> {
+			migrations::v8::LockDetailsV8 {
+				tx_id,
+				sender: ACCOUNT_A,
+				recipient: ACCOUNT_B,
+				asset: SwapAsset::Asset(ASSET_A),
+				amount: 1_000,
+				hashlock: [0; 32],
+				hash_algorithm: HashAlgorithm::Sha2_256,
+				expiry: Expiry::AtBlock(6),
+				expiration_block: 6,
+				status,
+			}
+		};
+		let (pending, claimed, refunded) = ([1; 32], [2; 32], [3; 32]);
+		for (tx_id, status) in [
+			(pending, SwapStatus::Pending),
+			(claimed, SwapStatus::Claimed { at: 7 }),
+			(refunded, SwapStatus::Refunded { at: 4 }),
+		] {
+			migrations::v8::LockTransactions::<Test>::insert(tx_id, v8_lock(tx_id, status));
+		}
+		StorageVersion::new(8).put::<Aswap>();
+		System::set_block_number(20);
+
+		run_migration::<migrations::v9::MigrateToV9<Test>>();
+		assert_eq!(StorageVersion::get::<Aswap>(), StorageVersion::new(9));
+		assert!(SettledLocks::<Test>::contains_key(7, claimed));
+		assert!(SettledLocks::<Test>::contains_key(4, refunded));
+		assert_eq!(SettledLocks::<Test>::iter_keys().count(), 2);
+		assert_eq!(PruneCursor::<Test>::get(), 4);
 	});
 }
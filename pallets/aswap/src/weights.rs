@@ -0,0 +1,159 @@
+//! Weights for pallet_aswap
+//!
+//! These weights are written by hand, they are not the output of the benchmarks. The execution
+//! times are estimates and the storage items listed for each call follow a reading of the code,
+//! so they may not match the real cost of the calls. Generate the weights of a runtime from
+//! `benchmarking.rs` before relying on them for fees:
+//!
+//! ./target/release/node-template benchmark pallet --chain=dev --steps=50 --repeat=20
+//! --pallet=pallet_aswap --extrinsic=* --execution=wasm --wasm-execution=compiled
+//! --output=runtime/src/weights/pallet_aswap.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+
+/// Weight functions needed for pallet_aswap.
+pub trait WeightInfo {
+	fn lock() -> Weight;
+	fn lock_native() -> Weight;
+	fn unlock(p: u32, ) -> Weight;
+	fn cancel() -> Weight;
+	fn refund_expired(n: u32, ) -> Weight;
+	fn pause() -> Weight;
+	fn unpause() -> Weight;
+	fn set_asset_policy() -> Weight;
+	fn set_open_lock_limit() -> Weight;
+	fn set_fee_policy() -> Weight;
+	fn unlock_for(p: u32, ) -> Weight;
+	fn remove_asset_policy() -> Weight;
+	fn reap() -> Weight;
+	fn prune_settled(n: u32, ) -> Weight;
+}
+
+/// Hand-written weights, used by the tests and by the runtime until benchmarked weights are
+/// generated for it.
+impl WeightInfo for () {
+	// reads Paused, LockTransactions, LockNonces, PrunedTxIds, OpenLockCount, OpenLockLimits,
+	//   LocksByHashlock, AssetPolicies, FeePolicies, TotalLocked, Assets::Asset,
+	//   Assets::Account x2, System::Account
+	// writes LockTransactions, LockNonces, ExpiringLocks, LocksBySender, OpenLockCount,
+	//   LocksByRecipient, LocksByHashlock, TotalLocked, Assets::Asset, Assets::Account x2,
+	//   System::Account
+	fn lock() -> Weight {
+		Weight::from_ref_time(85_000_000)
+			.saturating_add(RocksDbWeight::get().reads(14))
+			.saturating_add(RocksDbWeight::get().writes(12))
+	}
+	// reads Paused, LockTransactions, LockNonces, PrunedTxIds, OpenLockCount, OpenLockLimits,
+	//   LocksByHashlock, AssetPolicies, FeePolicies, TotalLocked, System::Account x2
+	// writes LockTransactions, LockNonces, ExpiringLocks, LocksBySender, OpenLockCount,
+	//   LocksByRecipient, LocksByHashlock, TotalLocked, System::Account x2
+	fn lock_native() -> Weight {
+		Weight::from_ref_time(75_000_000)
+			.saturating_add(RocksDbWeight::get().reads(12))
+			.saturating_add(RocksDbWeight::get().writes(10))
+	}
+	// reads Paused, LockTransactions, OpenLockCount, TotalLocked, Assets::Asset,
+	//   Assets::Account x3, System::Account
+	// writes LockTransactions, ExpiringLocks, LocksBySender, OpenLockCount, LocksByRecipient,
+	//   LocksByHashlock, TotalLocked, SettledLocks, Assets::Asset, Assets::Account x3,
+	//   KnownSecrets, System::Account
+	/// The range of component `p` is `[1, 256]`.
+	fn unlock(p: u32, ) -> Weight {
+		Weight::from_ref_time(59_472_000)
+			.saturating_add(Weight::from_ref_time(5_381).saturating_mul(p.into()))
+			.saturating_add(RocksDbWeight::get().reads(9))
+			.saturating_add(RocksDbWeight::get().writes(14))
+	}
+	// reads Paused, LockTransactions, OpenLockCount, TotalLocked, Assets::Asset,
+	//   Assets::Account x2, System::Account
+	// writes LockTransactions, ExpiringLocks, LocksBySender, OpenLockCount, LocksByRecipient,
+	//   LocksByHashlock, TotalLocked, SettledLocks, Assets::Asset, Assets::Account x2,
+	//   System::Account
+	fn cancel() -> Weight {
+		Weight::from_ref_time(50_000_000)
+			.saturating_add(RocksDbWeight::get().reads(8))
+			.saturating_add(RocksDbWeight::get().writes(12))
+	}
+	// reads ExpiringLocks, LockTransactions, OpenLockCount, TotalLocked, Assets::Asset,
+	//   Assets::Account x2, System::Account
+	// writes ExpiringLocks, LockTransactions, LocksBySender, OpenLockCount, LocksByRecipient,
+	//   LocksByHashlock, TotalLocked, SettledLocks, Assets::Asset, Assets::Account x2,
+	//   System::Account
+	/// The range of component `n` is `[1, 100]`.
+	fn refund_expired(n: u32, ) -> Weight {
+		Weight::from_ref_time(14_312_000)
+			.saturating_add(Weight::from_ref_time(47_905_000).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().reads((7_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes((12_u64).saturating_mul(n.into())))
+	}
+	// writes Paused
+	fn pause() -> Weight {
+		Weight::from_ref_time(12_000_000)
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	// reads Paused
+	// writes Paused
+	fn unpause() -> Weight {
+		Weight::from_ref_time(14_000_000)
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	// writes AssetPolicies
+	fn set_asset_policy() -> Weight {
+		Weight::from_ref_time(14_000_000)
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	// writes OpenLockLimits
+	fn set_open_lock_limit() -> Weight {
+		Weight::from_ref_time(13_000_000)
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	// writes FeePolicies
+	fn set_fee_policy() -> Weight {
+		Weight::from_ref_time(13_000_000)
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	// reads Paused, LockTransactions, System::BlockHash, OpenLockCount, TotalLocked,
+	//   Assets::Asset, Assets::Account x4, System::Account
+	// writes LockTransactions, ExpiringLocks, LocksBySender, OpenLockCount, LocksByRecipient,
+	//   LocksByHashlock, TotalLocked, SettledLocks, Assets::Asset, Assets::Account x4,
+	//   KnownSecrets, System::Account
+	/// The range of component `p` is `[1, 256]`.
+	fn unlock_for(p: u32, ) -> Weight {
+		Weight::from_ref_time(105_839_000)
+			.saturating_add(Weight::from_ref_time(5_412).saturating_mul(p.into()))
+			.saturating_add(RocksDbWeight::get().reads(11))
+			.saturating_add(RocksDbWeight::get().writes(15))
+	}
+	// reads AssetPolicies
+	// writes AssetPolicies
+	fn remove_asset_policy() -> Weight {
+		Weight::from_ref_time(16_000_000)
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	// reads LockTransactions, System::Account
+	// writes LockTransactions, SettledLocks, KnownSecrets, OversizedSecrets, PrunedTxIds,
+	//   System::Account
+	fn reap() -> Weight {
+		Weight::from_ref_time(25_000_000)
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(6))
+	}
+	// reads SettledLocks, LockTransactions, System::Account
+	// writes SettledLocks, LockTransactions, KnownSecrets, OversizedSecrets, PrunedTxIds,
+	//   System::Account
+	/// The range of component `n` is `[1, 100]`.
+	fn prune_settled(n: u32, ) -> Weight {
+		Weight::from_ref_time(12_540_000)
+			.saturating_add(Weight::from_ref_time(17_318_000).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes((6_u64).saturating_mul(n.into())))
+	}
+}
@@ -16,8 +16,8 @@ use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	traits::{
-		AccountIdLookup, BlakeTwo256, Block as BlockT, Convert, IdentifyAccount, NumberFor, One,
-		Verify,
+		AccountIdConversion, AccountIdLookup, BlakeTwo256, Block as BlockT, Convert,
+		IdentifyAccount, NumberFor, One, Verify,
 	},
 	transaction_validity::{TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature,
@@ -33,8 +33,8 @@ use frame_system::{EnsureRoot, EnsureSigned};
 pub use frame_support::{
 	construct_runtime, parameter_types,
 	traits::{
-		AsEnsureOriginWithArg, ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem,
-		Randomness, StorageInfo,
+		AsEnsureOriginWithArg, ConstBool, ConstU128, ConstU32, ConstU64, ConstU8,
+		KeyOwnerProofSystem, Randomness, StorageInfo,
 	},
 	weights::{
 		constants::{
@@ -65,6 +65,12 @@ pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::Account
 /// Balance of an account.
 pub type Balance = u128;
 
+/// Identifier of an asset of pallet-assets.
+pub type AssetId = u32;
+
+/// A timestamp: milliseconds since the unix epoch.
+pub type Moment = u64;
+
 /// Index of a transaction in the chain.
 pub type Index = u32;
 
@@ -107,10 +113,12 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	// Bumped for the upgrade that ships the aswap storage migrations in `Migrations`.
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	// Bumped with the same upgrade: it changes the arguments of the aswap `lock` call.
+	transaction_version: 2,
 	state_version: 1,
 };
 
@@ -235,8 +243,7 @@ impl pallet_grandpa::Config for Runtime {
 }
 
 impl pallet_timestamp::Config for Runtime {
-	/// A timestamp: milliseconds since the unix epoch.
-	type Moment = u64;
+	type Moment = Moment;
 	type OnTimestampSet = Aura;
 	type MinimumPeriod = ConstU64<{ SLOT_DURATION / 2 }>;
 	type WeightInfo = ();
@@ -247,7 +254,7 @@ pub const EXISTENTIAL_DEPOSIT: u128 = 500;
 
 impl pallet_balances::Config for Runtime {
 	type MaxLocks = ConstU32<50>;
-	type MaxReserves = ();
+	type MaxReserves = ConstU32<8>;
 	type ReserveIdentifier = [u8; 8];
 	/// The type for recording an account's balance.
 	type Balance = Balance;
@@ -288,8 +295,8 @@ parameter_types! {
 impl pallet_assets::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type Balance = u128;
-	type AssetId = u32;
-	type AssetIdParameter = codec::Compact<u32>;
+	type AssetId = AssetId;
+	type AssetIdParameter = codec::Compact<AssetId>;
 	type Currency = Balances;
 	type CreateOrigin = AsEnsureOriginWithArg<EnsureSigned<AccountId>>;
 	type ForceOrigin = EnsureRoot<AccountId>;
@@ -299,7 +306,7 @@ impl pallet_assets::Config for Runtime {
 	type MetadataDepositPerByte = MetadataDepositPerByte;
 	type ApprovalDeposit = ApprovalDeposit;
 	type StringLimit = StringLimit;
-	type Freezer = ();
+	type Freezer = Aswap;
 	type Extra = ();
 	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
 	type RemoveItemsLimit = ConstU32<1000>;
@@ -320,6 +327,8 @@ impl Convert<AuraId, AccountId> for AuthorityToAccount {
 
 parameter_types! {
 	pub const AswapPalletId: PalletId = PalletId(*b"aswapjur");
+	pub const LockCustody: pallet_aswap::Custody = pallet_aswap::Custody::PalletAccount;
+	pub AswapFeeDestination: AccountId = PalletId(*b"aswapfee").into_account_truncating();
 }
 
 /// Configure the pallet-aswap in pallets/aswap.
@@ -328,6 +337,58 @@ impl pallet_aswap::Config for Runtime {
 	type Currency = Balances;
 	type Fungibles = Assets;
 	type PalletId = AswapPalletId;
+	type DepositBase = ConstU128<10_000>;
+	type DepositPerByte = ConstU128<100>;
+	type StrictSecretLength = ConstBool<false>;
+	type MaxPreimageLen = ConstU32<256>;
+	type MaxLegacyPreimageLen = ConstU32<4096>;
+	type MinTimelock = ConstU32<{ 30 * MINUTES }>;
+	type MaxTimelock = ConstU32<{ 7 * DAYS }>;
+	type MinTimelockMargin = ConstU32<{ 30 * MINUTES }>;
+	type DeriveTxIds = ConstBool<true>;
+	type Time = Timestamp;
+	type ExpectedBlockTime = ConstU64<MILLISECS_PER_BLOCK>;
+	type ClaimBeforeExpiry = ConstBool<true>;
+	type PauseOrigin = EnsureRoot<AccountId>;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type RequireAssetPolicy = ConstBool<true>;
+	type MaxAutoRefundsPerBlock = ConstU32<50>;
+	type RetentionPeriod = ConstU32<{ 30 * DAYS }>;
+	type MaxOpenLocksPerAccount = ConstU32<20>;
+	type KeepSenderAlive = ConstBool<false>;
+	type LockCustody = LockCustody;
+	type FeeDestination = AswapFeeDestination;
+	type UnsignedPriority = ConstU64<{ 1 << 20 }>;
+	type OffchainSignature = Signature;
+	type OffchainPublic = <Signature as Verify>::Signer;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = AswapBenchmarkHelper;
+	// hand-written weights of the pallet, no benchmark has been run for this runtime yet:
+	// generate `weights/pallet_aswap.rs` with `benchmark pallet` and use it here before
+	// relying on the fees of the aswap calls
+	type WeightInfo = ();
+}
+
+/// Signs the `unlock_for` claims of the aswap benchmarks with a sr25519 key of the keystore.
+#[cfg(feature = "runtime-benchmarks")]
+pub struct AswapBenchmarkHelper;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_aswap::BenchmarkHelper<<Signature as Verify>::Signer, Signature>
+	for AswapBenchmarkHelper
+{
+	fn signer() -> <Signature as Verify>::Signer {
+		frame_support::sp_io::crypto::sr25519_generate(KeyTypeId(*b"aswp"), None).into()
+	}
+	fn sign(signer: &<Signature as Verify>::Signer, message: &[u8]) -> Signature {
+		match signer {
+			sp_runtime::MultiSigner::Sr25519(public) =>
+				frame_support::sp_io::crypto::sr25519_sign(KeyTypeId(*b"aswp"), public, message)
+					.expect("signer was generated in the keystore")
+					.into(),
+			_ => unreachable!("signer is a sr25519 key"),
+		}
+	}
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -374,6 +435,22 @@ pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
+/// Migrations to apply on runtime upgrade.
+pub type Migrations = (
+	pallet_aswap::migrations::v1::MigrateToV1<Runtime>,
+	pallet_aswap::migrations::v2::MigrateToV2<Runtime>,
+	pallet_aswap::migrations::v3::MigrateToV3<Runtime>,
+	pallet_aswap::migrations::v4::MigrateToV4<Runtime>,
+	pallet_aswap::migrations::v5::MigrateToV5<Runtime>,
+	pallet_aswap::migrations::v6::MigrateToV6<Runtime>,
+	pallet_aswap::migrations::v7::MigrateToV7<Runtime>,
+	pallet_aswap::migrations::v8::MigrateToV8<Runtime>,
+	pallet_aswap::migrations::v9::MigrateToV9<Runtime>,
+	pallet_aswap::migrations::v10::MigrateToV10<Runtime>,
+	pallet_aswap::migrations::v11::MigrateToV11<Runtime>,
+	pallet_aswap::migrations::v12::MigrateToV12<Runtime>,
+	pallet_aswap::migrations::v13::MigrateToV13<Runtime>,
+);
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -381,6 +458,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -395,9 +473,6 @@ mod benches {
 		[pallet_balances, Balances]
 		[pallet_timestamp, Timestamp]
 		[pallet_aswap, Aswap]
-		[pallet_dpos, Dpos]
-		[pallet_voting, Voting]
-
 	);
 }
 
@@ -532,6 +607,35 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_aswap_runtime_api::AswapApi<Block, AccountId, AssetId, Balance, BlockNumber, Moment>
+		for Runtime {
+		fn lock_details(
+			tx_id: [u8; 32],
+		) -> Option<pallet_aswap::LockDetails<Balance, AssetId, AccountId, BlockNumber, Moment>> {
+			Aswap::lock_transactions(tx_id)
+		}
+		fn known_secret(tx_id: [u8; 32]) -> Option<Vec<u8>> {
+			Aswap::known_secrets(tx_id)
+				.map(Into::into)
+				.or_else(|| Aswap::oversized_secrets(tx_id).map(Into::into))
+		}
+		fn locks_for_account(
+			account: AccountId,
+			cursor: Option<[u8; 32]>,
+			limit: u32,
+		) -> Vec<pallet_aswap::LockDetails<Balance, AssetId, AccountId, BlockNumber, Moment>> {
+			Aswap::locks_for_account(&account, cursor, limit)
+		}
+		fn status(tx_id: [u8; 32]) -> Option<pallet_aswap::LockStatus> {
+			Aswap::lock_status(tx_id)
+		}
+		fn audit(
+			asset: pallet_aswap::SwapAsset<AssetId>,
+		) -> pallet_aswap::CustodyAudit<Balance> {
+			Aswap::audit(asset)
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentCallApi<Block, Balance, RuntimeCall>
 		for Runtime
 	{